@@ -0,0 +1,516 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::iter::FromIterator;
+use std::ops::Add;
+use std::str::FromStr;
+
+use cube::Cube;
+use moves::{format_moves, Amount, Axis, Face, Metric, Move, ParseError, ParseErrorKind, FACES};
+
+/// The most times a bracketed group can be repeated when parsing.
+pub const MAX_REPEAT: usize = 100;
+
+/// The most moves parsing may expand groups into, so that a mistyped
+/// count can't use up all the memory.
+pub const MAX_PARSED_MOVES: usize = 10_000;
+
+/// A sequence of moves that can be manipulated as a whole.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Algorithm {
+    moves: Vec<Move>,
+}
+
+impl Algorithm {
+    pub fn new(moves: Vec<Move>) -> Self {
+        Algorithm { moves }
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn into_moves(self) -> Vec<Move> {
+        self.moves
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Length in the half turn metric: every face turn counts as one,
+    /// rotations are free.
+    pub fn htm(&self) -> usize {
+        self.length(Metric::Htm)
+    }
+
+    /// Length in the quarter turn metric: half turns count as two,
+    /// rotations are free.
+    pub fn qtm(&self) -> usize {
+        self.length(Metric::Qtm)
+    }
+
+    /// Length counted in `metric`.
+    pub fn length(&self, metric: Metric) -> usize {
+        self.moves.iter().map(|&mv| metric.cost(mv)).sum()
+    }
+
+    /// Length in the execution turn metric: every move, rotations
+    /// included, counts as one.
+    pub fn etm(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// The algorithm that undoes this one: every move inverted, in
+    /// reverse order.
+    pub fn inverse(&self) -> Algorithm {
+        self.moves.iter().rev().map(|mv| mv.inverse()).collect()
+    }
+
+    /// The algorithm with left and right swapped, so `R U R'` becomes
+    /// `L' U' L`.
+    pub fn mirror_left_right(&self) -> Algorithm {
+        self.mirror(Axis::X)
+    }
+
+    /// The algorithm with front and back swapped, so `F R F'` becomes
+    /// `B' R' B`.
+    pub fn mirror_front_back(&self) -> Algorithm {
+        self.mirror(Axis::Z)
+    }
+
+    fn mirror(&self, axis: Axis) -> Algorithm {
+        self.moves.iter().map(|mv| mv.mirror(axis)).collect()
+    }
+
+    /// Rewrites the algorithm for the cube as it was held before
+    /// `rotation`, so that doing the result has the same effect as doing
+    /// `rotation`, then this algorithm, then undoing `rotation`.
+    pub fn conjugate(&self, rotation: &Algorithm) -> Algorithm {
+        // Follow each face to where the rotation takes it.
+        let moved_to = |face: Face| {
+            rotation.moves.iter().fold(face, |face, &mv| match mv {
+                Move::Rotate(axis, amount) => {
+                    (0..amount.quarter_turns()).fold(face, |face, _| face.rotated(axis))
+                }
+                Move::Turn(..) => face,
+            })
+        };
+        let came_from = |face: Face| {
+            *FACES
+                .iter()
+                .find(|&&original| moved_to(original) == face)
+                .expect("rotations move every face somewhere")
+        };
+        self.moves
+            .iter()
+            .map(|&mv| match mv {
+                Move::Turn(face, amount) => Move::Turn(came_from(face), amount),
+                Move::Rotate(axis, amount) => {
+                    let face = came_from(axis.face());
+                    if face.axis().face() == face {
+                        Move::Rotate(face.axis(), amount)
+                    } else {
+                        Move::Rotate(face.axis(), amount.inverse())
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The same algorithm with turns of the same face next to each other
+    /// merged into one, e.g. `R R` becomes `R2` and `U U'` disappears.
+    pub fn simplified(&self) -> Algorithm {
+        let mut moves: Vec<Move> = Vec::with_capacity(self.moves.len());
+        for &mv in &self.moves {
+            match (moves.last().cloned(), mv) {
+                (Some(Move::Turn(face, before)), Move::Turn(turned, amount)) if face == turned => {
+                    moves.pop();
+                    let total = before.quarter_turns() + amount.quarter_turns();
+                    if let Some(amount) = Amount::from_quarter_turns(total) {
+                        moves.push(Move::Turn(face, amount));
+                    }
+                }
+                _ => moves.push(mv),
+            }
+        }
+        Algorithm::new(moves)
+    }
+
+    /// This algorithm followed by `other`.
+    pub fn then(&self, other: &Algorithm) -> Algorithm {
+        self.moves.iter().chain(&other.moves).cloned().collect()
+    }
+
+    /// This algorithm performed `times` times in a row.
+    pub fn repeat(&self, times: usize) -> Algorithm {
+        Algorithm::new(self.moves.repeat(times))
+    }
+
+    /// Performs every move of the algorithm on `cube`.
+    pub fn apply(&self, cube: &mut Cube) {
+        cube.apply_moves(&self.moves);
+    }
+}
+
+impl From<Vec<Move>> for Algorithm {
+    fn from(moves: Vec<Move>) -> Self {
+        Algorithm::new(moves)
+    }
+}
+
+impl FromIterator<Move> for Algorithm {
+    fn from_iter<I: IntoIterator<Item = Move>>(iter: I) -> Self {
+        Algorithm::new(iter.into_iter().collect())
+    }
+}
+
+impl Add<&Algorithm> for Algorithm {
+    type Output = Algorithm;
+
+    fn add(mut self, other: &Algorithm) -> Algorithm {
+        self.moves.extend_from_slice(&other.moves);
+        self
+    }
+}
+
+impl Add for Algorithm {
+    type Output = Algorithm;
+
+    fn add(self, other: Algorithm) -> Algorithm {
+        self + &other
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        write!(formatter, "{}", format_moves(&self.moves))
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = ParseError;
+
+    /// Parses standard notation such as `"R U R' U' F2 y"`.
+    ///
+    /// Moves may be separated by whitespace or written back to back
+    /// (`"RUR'U'"`). A half turn may be written `2` or `2'`. Moves can be
+    /// grouped in brackets and the group repeated with a count, inverted
+    /// with `'`, or both: `"(R U R' U')3"`, `"(R U)'"`.
+    /// Counts go up to `MAX_REPEAT`, and groups may expand to at most
+    /// `MAX_PARSED_MOVES` moves in all.
+    fn from_str(s: &str) -> Result<Algorithm, ParseError> {
+        let mut parser = Parser { text: s, posn: 0 };
+        parser.parse_sequence(None).map(Algorithm::new)
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    posn: usize,
+}
+
+fn is_suffix(c: char) -> bool {
+    c.is_ascii_digit() || c == '\'' || c == '’'
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.posn..].chars().next()
+    }
+
+    /// Consumes characters while `pred` holds and returns them.
+    fn take_while<F: Fn(char) -> bool>(&mut self, pred: F) -> &'a str {
+        let start = self.posn;
+        while let Some(c) = self.peek() {
+            if !pred(c) {
+                break;
+            }
+            self.posn += c.len_utf8();
+        }
+        &self.text[start..self.posn]
+    }
+
+    fn error(&self, kind: ParseErrorKind, position: usize, token: &str) -> ParseError {
+        ParseError {
+            kind,
+            position,
+            token: token.to_string(),
+        }
+    }
+
+    /// Parses moves up to the end of the text, or up to the `)` closing
+    /// the group opened at `open`.
+    fn parse_sequence(&mut self, open: Option<usize>) -> Result<Vec<Move>, ParseError> {
+        let mut moves = Vec::new();
+        loop {
+            self.take_while(char::is_whitespace);
+            let start = self.posn;
+            match self.peek() {
+                None => {
+                    return match open {
+                        Some(open) => Err(self.error(ParseErrorKind::UnclosedGroup, open, "(")),
+                        None => Ok(moves),
+                    };
+                }
+                Some(')') => {
+                    self.posn += 1;
+                    return match open {
+                        Some(_) => Ok(moves),
+                        None => Err(self.error(ParseErrorKind::UnexpectedClose, start, ")")),
+                    };
+                }
+                Some('(') => {
+                    self.posn += 1;
+                    let group = self.parse_sequence(Some(start))?;
+                    let group = self.parse_group_suffix(group)?;
+                    if moves.len() + group.len() > MAX_PARSED_MOVES {
+                        let token = &self.text[start..self.posn];
+                        return Err(self.error(ParseErrorKind::BadRepeat, start, token));
+                    }
+                    moves.extend(group);
+                }
+                Some(c) => {
+                    self.posn += c.len_utf8();
+                    self.take_while(is_suffix);
+                    let token = &self.text[start..self.posn];
+                    let mv = token.parse::<Move>().map_err(|mut err| {
+                        err.position = start;
+                        err
+                    })?;
+                    moves.push(mv);
+                }
+            }
+        }
+    }
+
+    /// Applies any repeat count and `'` written straight after a group.
+    /// Counts over `MAX_REPEAT`, or that would make more than
+    /// `MAX_PARSED_MOVES` moves, are refused.
+    fn parse_group_suffix(&mut self, group: Vec<Move>) -> Result<Vec<Move>, ParseError> {
+        let start = self.posn;
+        let count = self.take_while(|c| c.is_ascii_digit());
+        let times = if count.is_empty() {
+            1
+        } else {
+            match count.parse::<usize>() {
+                Ok(times) if times <= MAX_REPEAT && group.len() * times <= MAX_PARSED_MOVES => {
+                    times
+                }
+                _ => return Err(self.error(ParseErrorKind::BadRepeat, start, count)),
+            }
+        };
+        let inverted = self.take_while(|c| c == '\'' || c == '’');
+        let mut group = Algorithm::new(group);
+        match inverted {
+            "" => {}
+            "'" | "’" => group = group.inverse(),
+            _ => {
+                let token = &self.text[start..self.posn];
+                return Err(self.error(ParseErrorKind::BadRepeat, start, token));
+            }
+        }
+        Ok(group.repeat(times).into_moves())
+    }
+}
+
+/// A solved cube after `moves`, for tests.
+#[cfg(test)]
+pub fn cube_after(moves: &str) -> Cube {
+    let mut cube = Cube::new();
+    moves
+        .parse::<Algorithm>()
+        .expect("test moves are written correctly")
+        .apply(&mut cube);
+    cube
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{Algorithm, MAX_PARSED_MOVES};
+    use cube::Cube;
+    use moves::{Metric, ParseError, ParseErrorKind};
+
+    fn alg(text: &str) -> Algorithm {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_inverse() {
+        assert_eq!(alg("R U R' U' F2 y").inverse(), alg("y' F2 U R U' R'"));
+    }
+
+    #[test]
+    fn test_algorithm_then_inverse_is_identity() {
+        let sune = alg("R U R' U R U2 R'");
+        let mut cube = Cube::new();
+        sune.apply(&mut cube);
+        assert!(!cube.is_solved());
+        sune.inverse().apply(&mut cube);
+        assert_eq!(cube, Cube::new());
+    }
+
+    #[test]
+    fn test_mirror_left_right() {
+        assert_eq!(alg("R U R' U'").mirror_left_right(), alg("L' U' L U"));
+        assert_eq!(alg("F2 x y z").mirror_left_right(), alg("F2 x y' z'"));
+    }
+
+    #[test]
+    fn test_mirror_front_back() {
+        assert_eq!(alg("F R F'").mirror_front_back(), alg("B' R' B"));
+        assert_eq!(alg("x y z").mirror_front_back(), alg("x' y' z"));
+    }
+
+    #[test]
+    fn test_mirror_twice_is_original() {
+        let a = alg("R U2 F' L D B2 x y'");
+        assert_eq!(a.mirror_left_right().mirror_left_right(), a);
+        assert_eq!(a.mirror_front_back().mirror_front_back(), a);
+    }
+
+    #[test]
+    fn test_mirror_keeps_order() {
+        let sexy = alg("R U R' U'");
+        for mirrored in &[sexy.mirror_left_right(), sexy.mirror_front_back()] {
+            let mut cube = Cube::new();
+            for reps in 1..7 {
+                mirrored.apply(&mut cube);
+                assert_eq!(cube == Cube::new(), reps == 6, "{}", mirrored);
+            }
+        }
+    }
+
+    #[test]
+    fn test_conjugate() {
+        for rotation in &["", "x", "y2", "z'", "x y", "x2 y'"] {
+            let rotation = alg(rotation);
+            let a = alg("R U2 F' L D' B x y'");
+
+            let mut expected = Cube::new();
+            rotation
+                .then(&a)
+                .then(&rotation.inverse())
+                .apply(&mut expected);
+            let mut actual = Cube::new();
+            a.conjugate(&rotation).apply(&mut actual);
+
+            assert_eq!(actual, expected, "{}", rotation);
+        }
+        assert_eq!(alg("R U F").conjugate(&alg("y")), alg("B U R"));
+    }
+
+    #[test]
+    fn test_concatenation() {
+        assert_eq!(alg("R U").then(&alg("F")), alg("R U F"));
+        assert_eq!(alg("R U") + alg("F"), alg("R U F"));
+        assert_eq!(alg("R") + &alg(""), alg("R"));
+    }
+
+    #[test]
+    fn test_repeat() {
+        assert_eq!(alg("R U").repeat(3), alg("R U R U R U"));
+        assert!(alg("R U").repeat(0).is_empty());
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        assert_eq!(alg("(R U)3"), alg("R U R U R U"));
+        assert_eq!(alg("F (R U)' F'"), alg("F U' R' F'"));
+        assert_eq!(alg("(R (U F)2)2'"), alg("F' U' F' U' R' F' U' F' U' R'"));
+        assert_eq!(alg("()5 R"), alg("R"));
+    }
+
+    #[test]
+    fn test_parse_group_errors() {
+        assert_eq!(
+            "R (U F".parse::<Algorithm>(),
+            Err(ParseError {
+                kind: ParseErrorKind::UnclosedGroup,
+                position: 2,
+                token: "(".to_string(),
+            })
+        );
+        assert_eq!(
+            "R U) F".parse::<Algorithm>(),
+            Err(ParseError {
+                kind: ParseErrorKind::UnexpectedClose,
+                position: 3,
+                token: ")".to_string(),
+            })
+        );
+        assert_eq!(
+            "(R U)3'' F".parse::<Algorithm>(),
+            Err(ParseError {
+                kind: ParseErrorKind::BadRepeat,
+                position: 5,
+                token: "3''".to_string(),
+            })
+        );
+        assert_eq!(
+            "(R Q)".parse::<Algorithm>(),
+            Err(ParseError {
+                kind: ParseErrorKind::UnknownMove,
+                position: 3,
+                token: "Q".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_refuses_huge_repeats() {
+        let bad_repeat = |position: usize, token: &str| {
+            Err(ParseError {
+                kind: ParseErrorKind::BadRepeat,
+                position,
+                token: token.to_string(),
+            })
+        };
+        assert_eq!(
+            "(R U)18446744073709551615".parse::<Algorithm>(),
+            bad_repeat(5, "18446744073709551615")
+        );
+        assert_eq!(
+            "(R)99999999999".parse::<Algorithm>(),
+            bad_repeat(3, "99999999999")
+        );
+        assert_eq!("(R)101".parse::<Algorithm>(), bad_repeat(3, "101"));
+        assert_eq!(alg("((R)100)100").len(), MAX_PARSED_MOVES);
+        // Each count is allowed on its own, but together they multiply up.
+        assert_eq!(
+            "((((R)100)100)100)100".parse::<Algorithm>(),
+            bad_repeat(14, "100")
+        );
+        // So do groups side by side.
+        assert_eq!(
+            "(R)100 ".repeat(101).parse::<Algorithm>(),
+            bad_repeat(700, "(R)100")
+        );
+    }
+
+    #[test]
+    fn test_lengths_in_each_metric() {
+        let a = alg("R U2 F' y R2 x'");
+        assert_eq!(a.htm(), 4);
+        assert_eq!(a.qtm(), 6);
+        assert_eq!(a.etm(), 6);
+        assert_eq!(a.length(Metric::Htm), 4);
+        assert_eq!(a.length(Metric::Qtm), 6);
+    }
+
+    #[test]
+    fn test_simplified() {
+        assert_eq!(alg("R R U").simplified(), alg("R2 U"));
+        assert_eq!(alg("R U U' R2").simplified(), alg("R'"));
+        assert_eq!(alg("F2 F R y R").simplified(), alg("F' R y R"));
+        assert_eq!(alg("R U U' R'").simplified(), Algorithm::default());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(alg("(R U)2 F2'").to_string(), "R U R U F2");
+    }
+}
@@ -0,0 +1,524 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use algorithm::Algorithm;
+use cases::{self, is_one_color, is_solved_as_held, turns};
+use cll::is_first_layer_solved;
+use cube::{rotations, Color, Cube};
+use facelets::face_colors;
+use moves::Face;
+use pieces::{identify_corner, slot_colors};
+use solver::SolveError;
+use validate::validate;
+
+/// The colour beginners build the first layer in.
+pub const FIRST_COLOR: Color = Color::W;
+
+/// Drops the corner above the front right of the bottom layer into it, a
+/// third of a twist at a time. It leaves the rest of the bottom alone.
+pub const INSERT: &str = "R U R' U'";
+
+/// Twists three top corners, the front left one staying put.
+pub const SUNE: &str = "R U R' U R U2 R'";
+
+/// Swaps the two top corners on the right.
+pub const T_PERM: &str = "R U R' U' R' F R2 U' R' U' R U R' F'";
+
+/// The stages of the beginner's method, in the order they are done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Put the four corners of the first colour into the bottom layer,
+    /// one at a time.
+    FirstLayer,
+    /// Turn the top colour up on every top corner with Sunes.
+    OrientTop,
+    /// Swap top corners until the top layer matches the bottom.
+    PermuteTop,
+}
+
+pub const PHASES: [Phase; 3] = [Phase::FirstLayer, Phase::OrientTop, Phase::PermuteTop];
+
+impl Phase {
+    /// Whether `cube`, held as it is, is where this phase leaves it.
+    pub fn is_done(self, cube: &Cube) -> bool {
+        match self {
+            Phase::FirstLayer => is_first_layer_solved(cube),
+            Phase::OrientTop => is_first_layer_solved(cube) && is_one_color(cube, Face::U),
+            Phase::PermuteTop => is_solved_as_held(cube),
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        let name = match *self {
+            Phase::FirstLayer => "first layer",
+            Phase::OrientTop => "orient top",
+            Phase::PermuteTop => "permute top",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+/// One thing a beginner does: the piece or pieces it deals with, the moves
+/// and why they work.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub phase: Phase,
+    /// What the step deals with, e.g. "w-g-r corner" or "top corners".
+    pub piece: String,
+    pub algorithm: Algorithm,
+    /// What the moves do, in one line.
+    pub explanation: String,
+}
+
+impl Display for Step {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        write!(formatter, "{} ({}): ", self.phase, self.piece)?;
+        if self.algorithm.is_empty() {
+            write!(formatter, "nothing to do")?;
+        } else {
+            write!(formatter, "{}", self.algorithm)?;
+        }
+        write!(formatter, " - {}", self.explanation)
+    }
+}
+
+/// A solve the way a beginner does it, one step per piece or algorithm.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BeginnerSolution {
+    pub steps: Vec<Step>,
+}
+
+impl BeginnerSolution {
+    /// Every step's moves one after the other.
+    pub fn algorithm(&self) -> Algorithm {
+        self.steps
+            .iter()
+            .fold(Algorithm::default(), |algorithm, step| {
+                algorithm.then(&step.algorithm)
+            })
+    }
+}
+
+impl Display for BeginnerSolution {
+    /// Writes one line per step.
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        for step in &self.steps {
+            writeln!(formatter, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Solves `cube` layer by layer, the way it is first taught: build the
+/// `FIRST_COLOR` layer a corner at a time, turn the top colour up with
+/// Sunes, then swap top corners with T-perms. Solutions are very long,
+/// but need only three algorithms.
+pub fn solve(cube: Cube) -> Result<BeginnerSolution, SolveError> {
+    validate(&cube).map_err(SolveError::Invalid)?;
+    let mut cube = cube;
+    // The solved cube, held the same way as `cube`, for telling where
+    // each corner belongs.
+    let (hold, mut goal) = hold_first_corner(&mut cube);
+    let mut steps = vec![hold];
+    while let Some(step) = first_layer_corner(&mut cube, &mut goal) {
+        steps.push(step);
+    }
+    steps.extend(orient_top(&mut cube));
+    steps.extend(permute_top(&mut cube));
+    Ok(BeginnerSolution { steps })
+}
+
+/// Names a corner by its colours, starting with its U or D colour.
+fn corner_name(corner: u8) -> String {
+    let colors = slot_colors(&Cube::new(), corner as usize);
+    format!("{}-{}-{} corner", colors[0], colors[1], colors[2])
+}
+
+/// Which corner is at `slot`.
+fn corner_at(cube: &Cube, slot: usize) -> u8 {
+    identify_corner(slot_colors(cube, slot))
+        .expect("the cube has been checked")
+        .0
+}
+
+/// Where `corner` is.
+fn find_corner(cube: &Cube, corner: u8) -> usize {
+    (0..8)
+        .find(|&slot| corner_at(cube, slot) == corner)
+        .expect("the cube has been checked")
+}
+
+fn parse(notation: &str) -> Algorithm {
+    notation
+        .parse()
+        .expect("beginner algorithms are written correctly")
+}
+
+/// Turns of the whole cube about the vertical axis, fewest turns first.
+fn cube_turns() -> Vec<Algorithm> {
+    ["", "y", "y'", "y2"]
+        .iter()
+        .map(|turn| parse(turn))
+        .collect()
+}
+
+/// Turns the cube so that a corner with `FIRST_COLOR` facing down is at
+/// the bottom, and returns the step along with the solved cube held to
+/// match.
+fn hold_first_corner(cube: &mut Cube) -> (Step, Cube) {
+    let (rotation, slot) = rotations()
+        .into_iter()
+        .flat_map(|rotation| (4..8).map(move |slot| (rotation.clone(), slot)))
+        .find(|&(ref rotation, slot)| {
+            let mut held = *cube;
+            rotation.apply(&mut held);
+            held.block(slot).color(Face::D) == FIRST_COLOR
+        })
+        .expect("some corner has the first colour");
+    rotation.apply(cube);
+    let goal = rotations()
+        .iter()
+        .map(|rotation| {
+            let mut solved = Cube::new();
+            rotation.apply(&mut solved);
+            solved
+        })
+        .find(|solved| solved.block(slot) == cube.block(slot))
+        .expect("any corner can be held in any slot any way up");
+    let explanation = if rotation.is_empty() {
+        format!(
+            "it is already on the bottom with {} facing down, so the layer is built round it",
+            FIRST_COLOR
+        )
+    } else {
+        format!(
+            "turns the cube to hold it on the bottom with {} facing down; the layer is built round it",
+            FIRST_COLOR
+        )
+    };
+    let step = Step {
+        phase: Phase::FirstLayer,
+        piece: corner_name(corner_at(cube, slot)),
+        algorithm: rotation,
+        explanation,
+    };
+    (step, goal)
+}
+
+/// Puts the next bottom corner in place, turning the cube so its place is
+/// at the front right. Returns `None` once the bottom layer is done.
+fn first_layer_corner(cube: &mut Cube, goal: &mut Cube) -> Option<Step> {
+    let holds = cube_turns();
+    let next = holds.iter().find(|rotation| {
+        turned_by(cube, rotation).block(7) != turned_by(goal, rotation).block(7)
+    })?;
+    let corner = corner_at(&turned_by(goal, next), 7);
+    let mut algorithm = Algorithm::default();
+    let mut explanation = Vec::new();
+    let mut slot = find_corner(cube, corner);
+    if slot >= 4 && corner_at(goal, slot) != corner {
+        // It is in the bottom layer but in another corner's place.
+        let lift = holds
+            .iter()
+            .find(|lift| corner_at(&turned_by(goal, lift), 7) == corner_at(goal, slot))
+            .expect("some turn brings each bottom slot to the front");
+        let moves = lift.then(&parse(INSERT));
+        explanation.push(if lift.is_empty() {
+            format!("{} lifts it out of the wrong place", INSERT)
+        } else {
+            format!(
+                "{} brings it to the front right and {} lifts it out of the wrong place",
+                lift, INSERT
+            )
+        });
+        moves.apply(cube);
+        lift.apply(goal);
+        algorithm = algorithm.then(&moves);
+        slot = find_corner(cube, corner);
+    }
+    let rotation = holds
+        .iter()
+        .find(|rotation| corner_at(&turned_by(goal, rotation), 7) == corner)
+        .expect("some turn brings each bottom slot to the front");
+    if !rotation.is_empty() {
+        explanation.push(format!(
+            "{} turns the cube so its place is at the front right",
+            rotation
+        ));
+    }
+    rotation.apply(cube);
+    rotation.apply(goal);
+    algorithm = algorithm.then(rotation);
+    if slot < 4 {
+        let above = turns(Face::U)
+            .into_iter()
+            .find(|turn| {
+                let mut turned = *cube;
+                turn.apply(&mut turned);
+                corner_at(&turned, 3) == corner
+            })
+            .expect("some turn of U brings each top corner to the front right");
+        if !above.is_empty() {
+            explanation.push(format!("{} puts it above its place", above));
+        }
+        above.apply(cube);
+        algorithm = algorithm.then(&above);
+    }
+    let insert = parse(INSERT);
+    let mut times = 0;
+    while cube.block(7) != goal.block(7) {
+        assert!(
+            times < 6,
+            "{} always puts a corner in within 5 goes",
+            INSERT
+        );
+        insert.apply(cube);
+        algorithm = algorithm.then(&insert);
+        times += 1;
+    }
+    explanation.push(if slot < 4 {
+        format!(
+            "{} {} drops it in with {} facing down",
+            INSERT,
+            times_name(times),
+            FIRST_COLOR
+        )
+    } else {
+        format!("{} {} twists it round", INSERT, times_name(times))
+    });
+    Some(Step {
+        phase: Phase::FirstLayer,
+        piece: corner_name(corner),
+        algorithm,
+        explanation: explanation.join("; "),
+    })
+}
+
+/// `cube` turned by `rotation`, leaving `cube` as it is.
+fn turned_by(cube: &Cube, rotation: &Algorithm) -> Cube {
+    let mut turned = *cube;
+    rotation.apply(&mut turned);
+    turned
+}
+
+fn times_name(times: usize) -> String {
+    match times {
+        1 => "once".to_string(),
+        2 => "twice".to_string(),
+        _ => format!("{} times", times),
+    }
+}
+
+/// Does Sunes until the top colour faces up on every top corner. Where
+/// to hold the top for each Sune depends on how many corners face up.
+fn orient_top(cube: &mut Cube) -> Vec<Step> {
+    let top = opposite_color(FIRST_COLOR);
+    let mut steps = Vec::new();
+    let sune = parse(SUNE);
+    loop {
+        let up = (0..4)
+            .filter(|&slot| cube.block(slot).color(Face::U) == top)
+            .count();
+        if up == 4 {
+            break;
+        }
+        assert!(steps.len() < 4, "Sunes orient the top in a few goes");
+        // Where the front left corner's top colour should face.
+        let (face, why) = match up {
+            0 => (
+                Face::L,
+                "no corner faces up, so hold one showing it on the left",
+            ),
+            1 => (Face::U, "one corner faces up, so hold it at the front left"),
+            _ => (
+                Face::F,
+                "two corners face up, so hold one that doesn't showing it at the front",
+            ),
+        };
+        let turn = turns(Face::U)
+            .into_iter()
+            .find(|turn| {
+                let mut turned = *cube;
+                turn.apply(&mut turned);
+                turned.block(2).color(face) == top
+            })
+            .expect("some top corner shows the top colour that way");
+        let algorithm = turn.then(&sune);
+        algorithm.apply(cube);
+        let mut explanation = why.to_string();
+        if !turn.is_empty() {
+            explanation.push_str(&format!("; {} does that", turn));
+        }
+        explanation.push_str("; a Sune twists the other three");
+        steps.push(Step {
+            phase: Phase::OrientTop,
+            piece: "top corners".to_string(),
+            algorithm,
+            explanation,
+        });
+    }
+    if steps.is_empty() {
+        steps.push(Step {
+            phase: Phase::OrientTop,
+            piece: "top corners".to_string(),
+            algorithm: Algorithm::default(),
+            explanation: format!("{} already faces up on every top corner", top),
+        });
+    }
+    steps
+}
+
+/// The colour of the face opposite the one coloured `color` on a solved
+/// cube.
+fn opposite_color(color: Color) -> Color {
+    let solved = Cube::new();
+    let face = [Face::U, Face::D, Face::L, Face::R, Face::F, Face::B]
+        .iter()
+        .find(|&&face| solved.block(0).color(face) == color)
+        .expect("every colour is on some face");
+    solved.block(0).color(face.opposite())
+}
+
+/// Does T-perms until the top layer matches the bottom, then turns it into
+/// place.
+fn permute_top(cube: &mut Cube) -> Vec<Step> {
+    let t_perm = parse(T_PERM);
+    let mut steps = Vec::new();
+    loop {
+        if let Some(finish) = cases::finish(cube, &turns(Face::U), is_solved_as_held) {
+            if finish.is_empty() && !steps.is_empty() {
+                return steps;
+            }
+            finish.apply(cube);
+            let explanation = if finish.is_empty() {
+                "the top layer is already in place"
+            } else {
+                "the top layer is right, so turn it to match the bottom"
+            };
+            steps.push(Step {
+                phase: Phase::PermuteTop,
+                piece: "top layer".to_string(),
+                algorithm: finish,
+                explanation: explanation.to_string(),
+            });
+            return steps;
+        }
+        assert!(steps.len() < 2, "two T-perms are always enough");
+        // Two matching stickers on one side ("headlights") mean the other
+        // two corners need swapping.
+        let headlights = turns(Face::U).into_iter().find(|turn| {
+            let mut turned = *cube;
+            turn.apply(&mut turned);
+            let colors = face_colors(&turned, Face::L);
+            colors[0] == colors[1]
+        });
+        let (algorithm, explanation) = match headlights {
+            Some(turn) => {
+                let mut explanation = "one side has headlights".to_string();
+                if !turn.is_empty() {
+                    explanation.push_str(&format!(", so {} puts them on the left", turn));
+                }
+                explanation.push_str("; a T-perm swaps the two right corners");
+                (turn.then(&t_perm), explanation)
+            }
+            None => (
+                t_perm.clone(),
+                "no side has headlights; a T-perm swaps two corners to make some".to_string(),
+            ),
+        };
+        algorithm.apply(cube);
+        steps.push(Step {
+            phase: Phase::PermuteTop,
+            piece: "top corners".to_string(),
+            algorithm,
+            explanation,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{solve, Phase, PHASES};
+    use algorithm::Algorithm;
+    use cube::Cube;
+    use pieces::Pieces;
+    use scramble::Scrambler;
+    use solver::SolveError;
+
+    /// Applies each step in turn, checking every phase is done by the time
+    /// the next one starts.
+    fn check_steps(cube: Cube) {
+        let solution = solve(cube).unwrap();
+        let mut cube = cube;
+        let mut phases = PHASES.iter().peekable();
+        for step in &solution.steps {
+            while step.phase != **phases.peek().unwrap() {
+                assert!(phases.next().unwrap().is_done(&cube), "{}", solution);
+            }
+            step.algorithm.apply(&mut cube);
+        }
+        assert!(cube.is_solved(), "{}", solution);
+        assert!(Phase::PermuteTop.is_done(&cube));
+    }
+
+    #[test]
+    fn test_solved_cube_needs_nothing() {
+        let solution = solve(Cube::new()).unwrap();
+        assert!(solution.algorithm().is_empty(), "{}", solution);
+        assert_eq!(solution.steps.len(), 3);
+    }
+
+    #[test]
+    fn test_scrambles_are_solved_a_step_at_a_time() {
+        let mut scrambler = Scrambler::new(24);
+        for _ in 0..20 {
+            check_steps(scrambler.scramble().cube);
+        }
+        let mut turned = Cube::new();
+        "x y R U F'"
+            .parse::<Algorithm>()
+            .unwrap()
+            .apply(&mut turned);
+        check_steps(turned);
+    }
+
+    #[test]
+    fn test_every_top_twist_is_oriented() {
+        for twists in 0..27 {
+            let mut pieces = Pieces::solved();
+            pieces.orientation[0] = twists % 3;
+            pieces.orientation[1] = twists / 3 % 3;
+            pieces.orientation[2] = twists / 9;
+            pieces.orientation[3] = (6 - pieces.total_twist()) % 3;
+            check_steps(pieces.to_cube());
+        }
+    }
+
+    #[test]
+    fn test_steps_name_their_corner() {
+        let mut cube = Cube::new();
+        "R".parse::<Algorithm>().unwrap().apply(&mut cube);
+        let solution = solve(cube).unwrap();
+        let corners: Vec<&str> = solution
+            .steps
+            .iter()
+            .filter(|step| step.phase == Phase::FirstLayer)
+            .map(|step| step.piece.as_str())
+            .collect();
+        assert_eq!(corners.len(), 3, "{}", solution);
+        assert!(corners.iter().all(|piece| piece.starts_with("w-")));
+        assert!(solution.steps[1].explanation.contains("R U R' U'"));
+    }
+
+    #[test]
+    fn test_invalid_cube_is_refused() {
+        let mut pieces = Pieces::solved();
+        pieces.orientation[0] = 1;
+        match solve(pieces.to_cube()) {
+            Err(SolveError::Invalid(_)) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+}
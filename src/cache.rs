@@ -0,0 +1,383 @@
+use std::env;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use moves::{FACES, METRICS};
+use solver::MoveSet;
+
+/// Bumped whenever the layout of cache files or of the tables in them
+/// changes, so that old files are rebuilt rather than misread.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The puzzle cache files written here hold tables for.
+pub const PUZZLE: &str = "2x2x2";
+
+/// Every cache file starts with these bytes.
+const MAGIC: &[u8; 6] = b"RUSTIX";
+
+/// The file name ending of cache files.
+const EXTENSION: &str = "table";
+
+/// Which of the solver's tables a cache file holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableKind {
+    /// A `DistanceTable`.
+    Distance,
+    /// A set of `PruningTables`.
+    Pruning,
+}
+
+pub const TABLE_KINDS: [TableKind; 2] = [TableKind::Distance, TableKind::Pruning];
+
+impl Display for TableKind {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        let name = match *self {
+            TableKind::Distance => "distance",
+            TableKind::Pruning => "pruning",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+/// What a cache file says about the table in it, ahead of the table
+/// itself.
+///
+/// On disk the header is the bytes `RUSTIX`, then the format version as
+/// four little endian bytes, the puzzle name preceded by its length, one
+/// byte for the kind of table, one for the metric, the number of faces
+/// turned followed by each of them, and lastly the length and checksum of
+/// the table as four little endian bytes each. Kinds, metrics and faces
+/// are written as where they come in `TABLE_KINDS`, `METRICS` and
+/// `FACES`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: u32,
+    pub puzzle: String,
+    pub kind: TableKind,
+    pub moves: MoveSet,
+    /// The number of bytes in the table.
+    pub len: u32,
+    /// `checksum` of the table.
+    pub checksum: u32,
+}
+
+impl Display for Header {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        write!(
+            formatter,
+            "{} {} table for {} in {}, {} bytes, version {}",
+            self.puzzle,
+            self.kind,
+            self.moves,
+            self.moves.metric(),
+            self.len,
+            self.version
+        )
+    }
+}
+
+/// Why a cache file couldn't be used.
+#[derive(Debug)]
+pub enum CacheError {
+    /// The file couldn't be read or written.
+    Io(io::Error),
+    /// The file isn't a cache file, or is cut short.
+    Malformed,
+    /// The file was written in another version of the format.
+    Version(u32),
+    /// The file holds tables for another puzzle.
+    Puzzle(String),
+    /// The file holds another kind of table, or one for other moves.
+    WrongTable(TableKind, MoveSet),
+    /// The table doesn't match its checksum, so it has been damaged.
+    Checksum,
+}
+
+impl Display for CacheError {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        match *self {
+            CacheError::Io(ref err) => write!(formatter, "{}", err),
+            CacheError::Malformed => write!(formatter, "not a table cache file"),
+            CacheError::Version(version) => write!(
+                formatter,
+                "written in format version {} but this is version {}",
+                version, FORMAT_VERSION
+            ),
+            CacheError::Puzzle(ref puzzle) => {
+                write!(formatter, "holds tables for a {}, not a {}", puzzle, PUZZLE)
+            }
+            CacheError::WrongTable(kind, ref moves) => write!(
+                formatter,
+                "holds the {} table for {} in {}",
+                kind,
+                moves,
+                moves.metric()
+            ),
+            CacheError::Checksum => write!(formatter, "the table doesn't match its checksum"),
+        }
+    }
+}
+
+impl Error for CacheError {}
+
+impl From<io::Error> for CacheError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == ErrorKind::UnexpectedEof {
+            CacheError::Malformed
+        } else {
+            CacheError::Io(err)
+        }
+    }
+}
+
+/// The 32 bit FNV-1a hash of `data`.
+pub fn checksum(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Where to keep cache files unless told otherwise: `$RUSTIX_CACHE` if it
+/// is set, otherwise a `rustix` directory in the user's cache directory.
+pub fn default_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("RUSTIX_CACHE") {
+        return PathBuf::from(dir);
+    }
+    let cache = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(env::temp_dir);
+    cache.join("rustix")
+}
+
+/// The file in `dir` that holds the `kind` table for `moves`, e.g.
+/// `distance-URF-htm.table`.
+pub fn path(dir: &Path, kind: TableKind, moves: &MoveSet) -> PathBuf {
+    let faces: String = moves.faces().iter().map(|&face| char::from(face)).collect();
+    let metric = moves.metric().to_string().to_lowercase();
+    dir.join(format!("{}-{}-{}.{}", kind, faces, metric, EXTENSION))
+}
+
+/// Every cache file in `dir`, by name. A missing directory has none.
+pub fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(ref err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == EXTENSION) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Writes `data` as the `kind` table for `moves` to `path`, creating its
+/// directory if need be. The file is written under another name first and
+/// then renamed, so it is never left half written.
+pub fn write(path: &Path, kind: TableKind, moves: &MoveSet, data: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("partial");
+    {
+        let mut file = BufWriter::new(File::create(&partial)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        file.write_all(&[PUZZLE.len() as u8])?;
+        file.write_all(PUZZLE.as_bytes())?;
+        file.write_all(&[
+            index_of(&TABLE_KINDS, kind),
+            index_of(&METRICS, moves.metric()),
+        ])?;
+        file.write_all(&[moves.faces().len() as u8])?;
+        for &face in moves.faces() {
+            file.write_all(&[index_of(&FACES, face)])?;
+        }
+        file.write_all(&(data.len() as u32).to_le_bytes())?;
+        file.write_all(&checksum(data).to_le_bytes())?;
+        file.write_all(data)?;
+        file.flush()?;
+    }
+    fs::rename(&partial, path)
+}
+
+/// Reads the `kind` table for `moves` from `path`, checking that the file
+/// is one this version wrote for exactly that table and that it hasn't
+/// been damaged since.
+pub fn read(path: &Path, kind: TableKind, moves: &MoveSet) -> Result<Vec<u8>, CacheError> {
+    let (header, data) = read_any(path)?;
+    if header.kind != kind || header.moves != *moves {
+        return Err(CacheError::WrongTable(header.kind, header.moves));
+    }
+    Ok(data)
+}
+
+/// Reads whatever table is in `path`, checking its header and checksum.
+pub fn read_any(path: &Path) -> Result<(Header, Vec<u8>), CacheError> {
+    let mut file = BufReader::new(File::open(path)?);
+    let header = read_header(&mut file)?;
+    // The length isn't checked yet, so only as much as the file really
+    // holds is read, rather than allocating whatever it claims.
+    let mut data = Vec::new();
+    file.by_ref()
+        .take(u64::from(header.len))
+        .read_to_end(&mut data)?;
+    if data.len() != header.len as usize || file.read(&mut [0])? != 0 {
+        return Err(CacheError::Malformed);
+    }
+    if checksum(&data) != header.checksum {
+        return Err(CacheError::Checksum);
+    }
+    Ok((header, data))
+}
+
+fn read_header<R: Read>(file: &mut R) -> Result<Header, CacheError> {
+    let mut magic = [0; 6];
+    file.read_exact(&mut magic)?;
+    if magic != *MAGIC {
+        return Err(CacheError::Malformed);
+    }
+    let version = read_u32(file)?;
+    if version != FORMAT_VERSION {
+        return Err(CacheError::Version(version));
+    }
+    let mut puzzle = vec![0; usize::from(read_u8(file)?)];
+    file.read_exact(&mut puzzle)?;
+    let puzzle = String::from_utf8(puzzle).map_err(|_| CacheError::Malformed)?;
+    if puzzle != PUZZLE {
+        return Err(CacheError::Puzzle(puzzle));
+    }
+    let kind = *lookup(&TABLE_KINDS, read_u8(file)?)?;
+    let metric = *lookup(&METRICS, read_u8(file)?)?;
+    let mut faces = Vec::new();
+    for _ in 0..read_u8(file)? {
+        faces.push(*lookup(&FACES, read_u8(file)?)?);
+    }
+    Ok(Header {
+        version,
+        puzzle,
+        kind,
+        moves: MoveSet::new(&faces, metric),
+        len: read_u32(file)?,
+        checksum: read_u32(file)?,
+    })
+}
+
+fn read_u8<R: Read>(file: &mut R) -> Result<u8, CacheError> {
+    let mut byte = [0];
+    file.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u32<R: Read>(file: &mut R) -> Result<u32, CacheError> {
+    let mut bytes = [0; 4];
+    file.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn index_of<T: PartialEq>(items: &[T], item: T) -> u8 {
+    items.iter().position(|other| *other == item).unwrap() as u8
+}
+
+fn lookup<T>(items: &[T], index: u8) -> Result<&T, CacheError> {
+    items.get(usize::from(index)).ok_or(CacheError::Malformed)
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::{checksum, files, path, read, read_any, write, CacheError, TableKind};
+    use moves::{Face, Metric};
+    use solver::MoveSet;
+
+    /// An empty directory just for the calling test.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rustix-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(b""), 0x811c_9dc5);
+        assert_eq!(checksum(b"a"), 0xe40c_292c);
+        assert_ne!(checksum(&[1, 2]), checksum(&[2, 1]));
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = scratch_dir("round-trip");
+        let moves = MoveSet::new(&[Face::R, Face::U], Metric::Qtm);
+        let file = path(&dir, TableKind::Pruning, &moves);
+        assert_eq!(file, dir.join("pruning-UR-qtm.table"));
+        assert_eq!(files(&dir).unwrap(), Vec::<PathBuf>::new());
+
+        write(&file, TableKind::Pruning, &moves, &[3, 1, 4, 1, 5]).unwrap();
+        assert_eq!(files(&dir).unwrap(), vec![file.clone()]);
+        assert_eq!(
+            read(&file, TableKind::Pruning, &moves).unwrap(),
+            vec![3, 1, 4, 1, 5]
+        );
+        let (header, _) = read_any(&file).unwrap();
+        assert_eq!(header.moves, moves);
+        assert_eq!(header.len, 5);
+
+        match read(&file, TableKind::Distance, &moves) {
+            Err(CacheError::WrongTable(TableKind::Pruning, ref found)) if *found == moves => {}
+            other => panic!("read the wrong table: {:?}", other),
+        }
+        let all = MoveSet::all(Metric::Qtm);
+        assert!(read(&file, TableKind::Pruning, &all).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_damaged_caches_are_rejected() {
+        let dir = scratch_dir("damaged");
+        let moves = MoveSet::all(Metric::Htm);
+        let file = path(&dir, TableKind::Distance, &moves);
+        write(&file, TableKind::Distance, &moves, &[0, 1, 2, 3]).unwrap();
+        let good = fs::read(&file).unwrap();
+
+        let mut flipped = good.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        fs::write(&file, &flipped).unwrap();
+        assert!(matches!(read_any(&file), Err(CacheError::Checksum)));
+
+        fs::write(&file, &good[..good.len() - 1]).unwrap();
+        assert!(matches!(read_any(&file), Err(CacheError::Malformed)));
+
+        // The length comes just before the checksum and the data.
+        let mut huge = good.clone();
+        let len_at = good.len() - 4 - 4 - 4;
+        huge[len_at..len_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&file, &huge).unwrap();
+        assert!(matches!(read_any(&file), Err(CacheError::Malformed)));
+
+        let mut old = good.clone();
+        old[6] = 0;
+        fs::write(&file, &old).unwrap();
+        assert!(matches!(read_any(&file), Err(CacheError::Version(0))));
+
+        fs::write(&file, b"not a table").unwrap();
+        assert!(matches!(read_any(&file), Err(CacheError::Malformed)));
+
+        fs::remove_file(&file).unwrap();
+        assert!(matches!(read_any(&file), Err(CacheError::Io(_))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use algorithm::Algorithm;
+use cube::Cube;
+use facelets::face_colors;
+use moves::{Face, Move, AMOUNTS, FACES};
+
+/// A situation a layer can be in, named as cubers name it, along with the
+/// algorithm that deals with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Case {
+    pub name: &'static str,
+    /// How to tell the case apart from the others.
+    pub description: &'static str,
+    /// The algorithm in standard notation, for the case held the way it
+    /// expects. Turning U or D first may be needed to get it that way.
+    pub notation: &'static str,
+}
+
+impl Case {
+    pub fn algorithm(&self) -> Algorithm {
+        self.notation
+            .parse()
+            .expect("case algorithms are written correctly")
+    }
+}
+
+/// Which case a cube is in, and the turns around its algorithm that get
+/// the job done.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recognition<'a> {
+    pub case: &'a Case,
+    /// Turns to do first to hold the case as its algorithm expects, such
+    /// as the pre-AUF (adjustment of the U face) of a last layer case.
+    pub setup: Algorithm,
+    /// Turns to do after the algorithm to finish off, such as the
+    /// post-AUF.
+    pub finish: Algorithm,
+}
+
+impl<'a> Recognition<'a> {
+    /// The setup, the case's algorithm and the finish, one after the
+    /// other.
+    pub fn algorithm(&self) -> Algorithm {
+        self.setup.then(&self.case.algorithm()).then(&self.finish)
+    }
+}
+
+/// Works out which of `cases` `cube` is in: the first one whose algorithm,
+/// after one of `setups` and followed by one of `finishes`, leaves `done`
+/// true. Setups and finishes are tried in order, so they should come
+/// shortest first. Doesn't check whether the cube needs any algorithm at
+/// all; see `finish`.
+pub fn recognize<'a, D>(
+    cube: &Cube,
+    cases: &'a [Case],
+    setups: &[Algorithm],
+    finishes: &[Algorithm],
+    done: D,
+) -> Option<Recognition<'a>>
+where
+    D: Fn(&Cube) -> bool,
+{
+    for case in cases {
+        let algorithm = case.algorithm();
+        for setup in setups {
+            let mut tried = *cube;
+            setup.apply(&mut tried);
+            algorithm.apply(&mut tried);
+            if let Some(finish) = finish(&tried, finishes, &done) {
+                return Some(Recognition {
+                    case,
+                    setup: setup.clone(),
+                    finish,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// The first of `finishes` that leaves `done` true.
+pub fn finish<D>(cube: &Cube, finishes: &[Algorithm], done: D) -> Option<Algorithm>
+where
+    D: Fn(&Cube) -> bool,
+{
+    finishes
+        .iter()
+        .find(|finish| {
+            let mut finished = *cube;
+            finish.apply(&mut finished);
+            done(&finished)
+        })
+        .cloned()
+}
+
+/// The four ways of turning `face`, starting with not turning it.
+pub fn turns(face: Face) -> Vec<Algorithm> {
+    let mut turns = vec![Algorithm::default()];
+    turns.extend(
+        AMOUNTS
+            .iter()
+            .map(|&amount| Algorithm::new(vec![Move::Turn(face, amount)])),
+    );
+    turns
+}
+
+/// Every way of turning the top and bottom layers, fewest turns first.
+pub fn layer_turns() -> Vec<Algorithm> {
+    let bottom = turns(Face::D);
+    let mut both: Vec<Algorithm> = turns(Face::U)
+        .iter()
+        .flat_map(|top| bottom.iter().map(move |bottom| top.then(bottom)))
+        .collect();
+    both.sort_by_key(Algorithm::len);
+    both
+}
+
+/// Whether every sticker on `face` is the same colour.
+pub fn is_one_color(cube: &Cube, face: Face) -> bool {
+    let colors = face_colors(cube, face);
+    colors.iter().all(|&color| color == colors[0])
+}
+
+/// Whether every face of `cube`, held as it is, is one colour. For cubes
+/// that can be solved this is the same as `Cube::is_solved`, only quicker.
+pub fn is_solved_as_held(cube: &Cube) -> bool {
+    FACES.iter().all(|&face| is_one_color(cube, face))
+}
+
+/// Whether the corners of the U or D layer are in the right places
+/// relative to each other, i.e. the layer shows one colour on each side.
+pub fn is_layer_solved(cube: &Cube, face: Face) -> bool {
+    // The sides are read with U at the top.
+    let row = if face == Face::U { 0 } else { 2 };
+    [Face::F, Face::R, Face::B, Face::L].iter().all(|&side| {
+        let colors = face_colors(cube, side);
+        colors[row] == colors[row + 1]
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{finish, is_layer_solved, is_solved_as_held, recognize, turns, Case};
+    use algorithm::{cube_after, Algorithm};
+    use cube::Cube;
+    use moves::Face;
+
+    const SEXY: [Case; 2] = [
+        Case {
+            name: "sledgehammer",
+            description: "",
+            notation: "R' F R F'",
+        },
+        Case {
+            name: "sexy move",
+            description: "",
+            notation: "R U R' U'",
+        },
+    ];
+
+    #[test]
+    fn test_recognize_tries_every_setup_and_finish() {
+        let cube = cube_after("R U' R' U2");
+        let recognition = recognize(
+            &cube,
+            &SEXY,
+            &turns(Face::U),
+            &turns(Face::U),
+            is_solved_as_held,
+        )
+        .unwrap();
+        assert_eq!(recognition.case.name, "sexy move");
+        assert_eq!(recognition.setup.to_string(), "U2");
+        assert_eq!(recognition.finish.to_string(), "U");
+        let mut solved = cube;
+        recognition.algorithm().apply(&mut solved);
+        assert!(solved.is_solved());
+
+        assert_eq!(
+            recognize(
+                &cube_after("R"),
+                &SEXY,
+                &turns(Face::U),
+                &turns(Face::U),
+                is_solved_as_held
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_finish() {
+        let finishes = turns(Face::U);
+        assert_eq!(
+            finish(&Cube::new(), &finishes, is_solved_as_held),
+            Some(Algorithm::default())
+        );
+        assert_eq!(
+            finish(&cube_after("U"), &finishes, is_solved_as_held).map(|finish| finish.to_string()),
+            Some("U'".to_string())
+        );
+        assert_eq!(finish(&cube_after("R"), &finishes, is_solved_as_held), None);
+    }
+
+    #[test]
+    fn test_layer_checks() {
+        let cube = cube_after("R U R' U' R' F R2 U' R' U' R U R' F'");
+        assert!(!is_layer_solved(&cube, Face::U));
+        assert!(is_layer_solved(&cube, Face::D));
+        assert!(is_layer_solved(&cube_after("U D'"), Face::U));
+        assert!(!is_solved_as_held(&cube_after("U D")));
+        assert!(is_solved_as_held(&cube_after("x y2")));
+    }
+}
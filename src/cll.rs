@@ -0,0 +1,343 @@
+use cases::{self, is_layer_solved, is_one_color, is_solved_as_held, turns, Case, Recognition};
+use cube::Cube;
+#[cfg(test)]
+use facelets::face_colors;
+use moves::Face;
+
+/// Every CLL case: the ways the top corners can be twisted and mixed up
+/// once the bottom layer is solved, apart from being solved already. Each
+/// is named after the OLL case its twists make, or after the swap needed
+/// if every corner faces up, and numbered within that.
+///
+/// Each description ends with the top layer's side stickers, held as the
+/// algorithm expects and read front, right, back then left, left to right
+/// on each side. `U` is the top colour. The first other sticker is `a`,
+/// and the rest are lettered by how their colour sits on a solved cube
+/// compared to that one: `b` the next side clockwise looking down on the
+/// top, `c` the opposite side and `d` the next side anticlockwise.
+pub const CLL_CASES: [Case; 42] = [
+    Case {
+        name: "Sune 1",
+        description: "opposite colours on the left; the sides read aU aU cU db",
+        notation: "L' U2 L U2 L F' L' F",
+    },
+    Case {
+        name: "Sune 2",
+        description: "neighbouring colours on the left; the sides read aU aU dU cb",
+        notation: "R U R' U R U2 R'",
+    },
+    Case {
+        name: "Sune 3",
+        description: "opposite colours on the left; the sides read aU cU aU db",
+        notation: "R2 F R U2 F U2 R' F' R2",
+    },
+    Case {
+        name: "Sune 4",
+        description: "neighbouring colours on the left; the sides read aU cU dU ab",
+        notation: "U2 R U R' U R' F R F' R U2 R'",
+    },
+    Case {
+        name: "Sune 5",
+        description: "neighbouring colours on the left; the sides read aU dU aU cb",
+        notation: "F R' F' R U2 R U2 R'",
+    },
+    Case {
+        name: "Sune 6",
+        description: "neighbouring colours on the left; the sides read aU dU cU ab",
+        notation: "R U' L' U R' U' L",
+    },
+    Case {
+        name: "Antisune 1",
+        description: "opposite colours on the back; the sides read Ua Ua db Uc",
+        notation: "F R2 F' U2 F' U F R'",
+    },
+    Case {
+        name: "Antisune 2",
+        description: "neighbouring colours on the back; the sides read Ua Ua dc Ub",
+        notation: "U' R' U' R U' R' U2 R",
+    },
+    Case {
+        name: "Antisune 3",
+        description: "neighbouring colours on the back; the sides read Ua Uc bc Ud",
+        notation: "R F' U2 F R' U2 R U R2",
+    },
+    Case {
+        name: "Antisune 4",
+        description: "opposite colours on the back; the sides read Ua Uc bd Uc",
+        notation: "F2 R U F2 R' F2 U' R' F2",
+    },
+    Case {
+        name: "Antisune 5",
+        description: "neighbouring colours on the back; the sides read Ua Ud cb Ud",
+        notation: "U F' R U R' U2 R' F2 R",
+    },
+    Case {
+        name: "Antisune 6",
+        description: "neighbouring colours on the back; the sides read Ua Ud cd Ub",
+        notation: "U L' U R U' L U R'",
+    },
+    Case {
+        name: "H 1",
+        description: "headlights on the right, headlights on the left; the sides read UU aa UU cc",
+        notation: "F R U R' U' R U R' U' R U R' U' F'",
+    },
+    Case {
+        name: "H 2",
+        description: "headlights on the right, opposite colours on the left; the sides read UU aa UU db",
+        notation: "R F R2 F U2 F R U2 F2",
+    },
+    Case {
+        name: "H 3",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read UU ab UU bc",
+        notation: "F U2 F2 R2 U' F U R' U R'",
+    },
+    Case {
+        name: "H 4",
+        description: "opposite colours on the right, opposite colours on the left; the sides read UU ac UU ca",
+        notation: "R2 U2 R U2 R2",
+    },
+    Case {
+        name: "Pi 1",
+        description: "headlights on the right; the sides read aU bb Uc UU",
+        notation: "R2 U F2 R' U2 R F2 U' R2",
+    },
+    Case {
+        name: "Pi 2",
+        description: "neighbouring colours on the right; the sides read aU bc Ub UU",
+        notation: "R F2 R' U2 F U' F2 U R F'",
+    },
+    Case {
+        name: "Pi 3",
+        description: "opposite colours on the right; the sides read aU ca Uc UU",
+        notation: "F R' F U' F' U F2 R2 U2 R'",
+    },
+    Case {
+        name: "Pi 4",
+        description: "headlights on the right; the sides read aU cc Ua UU",
+        notation: "U' R' F R U F U' R U R' U' F'",
+    },
+    Case {
+        name: "Pi 5",
+        description: "neighbouring colours on the right; the sides read aU da Ub UU",
+        notation: "R F' R' F' U F2 R' F U2 F'",
+    },
+    Case {
+        name: "Pi 6",
+        description: "opposite colours on the right; the sides read aU db Ua UU",
+        notation: "R U2 R2 U' R2 U' R2 U2 R",
+    },
+    Case {
+        name: "U 1",
+        description: "neighbouring colours on the front, opposite colours on the right, neighbouring colours on the back; the sides read ab ac bc UU",
+        notation: "F R U R' U' F'",
+    },
+    Case {
+        name: "U 2",
+        description: "neighbouring colours on the front, neighbouring colours on the right, neighbouring colours on the back; the sides read ab ad cb UU",
+        notation: "U R2 D' R U2 R' D R U2 R",
+    },
+    Case {
+        name: "U 3",
+        description: "opposite colours on the front, headlights on the right, opposite colours on the back; the sides read ac bb ac UU",
+        notation: "R U F U2 F R U2 R U F",
+    },
+    Case {
+        name: "U 4",
+        description: "opposite colours on the front, opposite colours on the right, opposite colours on the back; the sides read ac bd ca UU",
+        notation: "F U' R F' R' U F' U' R",
+    },
+    Case {
+        name: "U 5",
+        description: "neighbouring colours on the front, neighbouring colours on the right, neighbouring colours on the back; the sides read ad cb ab UU",
+        notation: "U' R2 D R' U2 R D' R' U2 R'",
+    },
+    Case {
+        name: "U 6",
+        description: "neighbouring colours on the front, headlights on the right, neighbouring colours on the back; the sides read ad cc ba UU",
+        notation: "F R' U F' U F U2 R2 F'",
+    },
+    Case {
+        name: "T 1",
+        description: "opposite colours on the right, headlights on the left; the sides read Ua db aU cc",
+        notation: "R F2 U R2 U F2 R F U2 F2",
+    },
+    Case {
+        name: "T 2",
+        description: "opposite colours on the right, opposite colours on the left; the sides read Ua db aU db",
+        notation: "R F' R' U F' U' R U' F",
+    },
+    Case {
+        name: "T 3",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read Ua dc bU bc",
+        notation: "R U R' U' R' F R F'",
+    },
+    Case {
+        name: "T 4",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read Ua dc bU da",
+        notation: "F R F' U' R' U' R",
+    },
+    Case {
+        name: "T 5",
+        description: "headlights on the right, headlights on the left; the sides read Ua dd cU bb",
+        notation: "R F U' F U2 F' R U F2",
+    },
+    Case {
+        name: "T 6",
+        description: "headlights on the right, opposite colours on the left; the sides read Ua dd cU ca",
+        notation: "R F2 U2 R' U' F R' F R'",
+    },
+    Case {
+        name: "L 1",
+        description: "neighbouring colours on the back, neighbouring colours on the left; the sides read Ua dU bc bc",
+        notation: "F U2 F' R U F2 U2 R F",
+    },
+    Case {
+        name: "L 2",
+        description: "opposite colours on the back, neighbouring colours on the left; the sides read Ua dU bd cb",
+        notation: "F R' F' R U R U' R'",
+    },
+    Case {
+        name: "L 3",
+        description: "neighbouring colours on the back, opposite colours on the left; the sides read Ua dU cb ac",
+        notation: "F R2 U R' U' R2 F'",
+    },
+    Case {
+        name: "L 4",
+        description: "neighbouring colours on the back, opposite colours on the left; the sides read Ua dU cd ca",
+        notation: "R U2 R U F' U2 F U' R2",
+    },
+    Case {
+        name: "L 5",
+        description: "opposite colours on the back, neighbouring colours on the left; the sides read Ua dU db ab",
+        notation: "R U2 R' U' F2 U' F' U F2",
+    },
+    Case {
+        name: "L 6",
+        description: "neighbouring colours on the back, neighbouring colours on the left; the sides read Ua dU dc ba",
+        notation: "F2 U' R F2 R' U2 R U' F2",
+    },
+    Case {
+        name: "adjacent swap",
+        description: "neighbouring colours on the front, opposite colours on the right, neighbouring colours on the back, headlights on the left; the sides read ad ca dc bb",
+        notation: "R U R' U' R' F R2 U' R' U' R U R' F'",
+    },
+    Case {
+        name: "diagonal swap",
+        description: "opposite colours on the front, opposite colours on the right, opposite colours on the back, opposite colours on the left; the sides read ac bd ca db",
+        notation: "F R U' R' U' R U R' F' R U R' U' R' F R F'",
+    },
+];
+
+/// Whether the bottom layer of `cube`, held as it is, is solved: one colour
+/// on the D face and on the bottom of each side. The colour doesn't matter.
+pub fn is_first_layer_solved(cube: &Cube) -> bool {
+    is_one_color(cube, Face::D) && is_layer_solved(cube, Face::D)
+}
+
+/// Works out which CLL case `cube` is in, whatever colour its bottom layer
+/// is. The recognition's setup is the pre-AUF and its finish the post-AUF.
+///
+/// Returns `None` if the bottom layer isn't solved, or if the cube needs
+/// nothing more than a turn of U.
+pub fn recognize(cube: &Cube) -> Option<Recognition<'static>> {
+    let aufs = turns(Face::U);
+    if !is_first_layer_solved(cube) || cases::finish(cube, &aufs, is_solved_as_held).is_some() {
+        return None;
+    }
+    cases::recognize(cube, &CLL_CASES, &aufs, &aufs, is_solved_as_held)
+}
+
+/// The top layer's side stickers of a case set up on `Cube::new()`,
+/// lettered the way `CLL_CASES` describes them, for tests.
+#[cfg(test)]
+pub fn side_letters(cube: &Cube) -> String {
+    let solved = Cube::new();
+    let top = face_colors(&solved, Face::U)[0];
+    // The sides clockwise looking down on the top.
+    let clockwise = [Face::F, Face::L, Face::B, Face::R];
+    let side_of = |color| {
+        clockwise
+            .iter()
+            .position(|&side| face_colors(&solved, side)[0] == color)
+            .expect("the top layer only shows side colours and the top colour")
+    };
+    let mut first = None;
+    let mut sides = Vec::new();
+    for &side in &[Face::F, Face::R, Face::B, Face::L] {
+        let mut letters = String::new();
+        for &color in &face_colors(cube, side)[..2] {
+            if color == top {
+                letters.push('U');
+            } else {
+                let a = *first.get_or_insert(side_of(color));
+                letters.push(['a', 'b', 'c', 'd'][(side_of(color) + 4 - a) % 4]);
+            }
+        }
+        sides.push(letters);
+    }
+    sides.join(" ")
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{is_first_layer_solved, recognize, CLL_CASES};
+    use algorithm::{cube_after, Algorithm};
+    use cases::{is_solved_as_held, turns};
+    use cube::Cube;
+    use moves::Face;
+
+    #[test]
+    fn test_library_has_every_case_once() {
+        for (i, case) in CLL_CASES.iter().enumerate() {
+            assert!(CLL_CASES[..i].iter().all(|other| other.name != case.name));
+            let mut cube = Cube::new();
+            case.algorithm().inverse().apply(&mut cube);
+            assert!(is_first_layer_solved(&cube), "{}", case.name);
+            assert!(!is_solved_as_held(&cube), "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn test_every_case_is_recognised_with_any_auf() {
+        let aufs = turns(Face::U);
+        for case in CLL_CASES.iter() {
+            for pre_auf in &aufs {
+                for post_auf in &aufs {
+                    let mut cube = Cube::new();
+                    pre_auf
+                        .then(&case.algorithm())
+                        .then(post_auf)
+                        .inverse()
+                        .apply(&mut cube);
+                    let recognition = recognize(&cube).unwrap();
+                    assert_eq!(recognition.case.name, case.name);
+                    recognition.algorithm().apply(&mut cube);
+                    assert!(is_solved_as_held(&cube), "{}", case.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_any_colour_on_the_bottom() {
+        for rotation in &["x", "z'", "x2 y"] {
+            for case in CLL_CASES.iter().step_by(5) {
+                let mut cube = cube_after(rotation);
+                case.algorithm().inverse().apply(&mut cube);
+                let recognition = recognize(&cube).unwrap();
+                assert_eq!(recognition.case.name, case.name);
+                assert_eq!(recognition.setup, Algorithm::default());
+            }
+        }
+    }
+
+    #[test]
+    fn test_recognize_needs_a_case() {
+        assert_eq!(recognize(&cube_after("R")), None);
+        assert_eq!(recognize(&cube_after("U2")), None);
+        let recognition = recognize(&cube_after("R U2 R' U' R U' R' U")).unwrap();
+        assert_eq!(recognition.case.name, "Sune 2");
+        assert_eq!(recognition.setup.to_string(), "U'");
+    }
+}
@@ -0,0 +1,99 @@
+use moves::Move;
+use pieces::Pieces;
+
+/// Number of ways to arrange the corners other than DBL.
+pub const PERMUTATIONS: usize = 5040;
+
+/// Number of ways to twist the corners other than DBL.
+pub const ORIENTATIONS: usize = 729;
+
+/// Precomputed effect of each of a set of moves on position numbers, so
+/// states can be moved around without building `Pieces` or `Cube`s.
+///
+/// The arrangement and twist of the corners are moved separately, as what
+/// a move does to one doesn't depend on the other. The moves must leave
+/// the DBL corner alone.
+pub struct MoveTables {
+    moves: Vec<Move>,
+    permutation: Vec<Vec<u16>>,
+    orientation: Vec<Vec<u16>>,
+}
+
+impl MoveTables {
+    pub fn new(moves: &[Move]) -> Self {
+        let effects: Vec<Pieces> = moves.iter().map(|&mv| Pieces::effect_of(mv)).collect();
+        let permutation = (0..PERMUTATIONS as u32)
+            .map(|rank| {
+                let mut pieces = Pieces::solved();
+                pieces.set_permutation_rank(rank);
+                effects
+                    .iter()
+                    .map(|effect| {
+                        let mut moved = pieces;
+                        moved.apply(effect);
+                        moved.permutation_rank() as u16
+                    })
+                    .collect()
+            })
+            .collect();
+        let orientation = (0..ORIENTATIONS as u32)
+            .map(|rank| {
+                let mut pieces = Pieces::solved();
+                pieces.set_orientation_rank(rank);
+                effects
+                    .iter()
+                    .map(|effect| {
+                        let mut moved = pieces;
+                        moved.apply(effect);
+                        moved.orientation_rank() as u16
+                    })
+                    .collect()
+            })
+            .collect();
+        MoveTables {
+            moves: moves.to_vec(),
+            permutation,
+            orientation,
+        }
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The position number after doing move number `mv` to the state
+    /// numbered `rank`.
+    pub fn apply(&self, rank: u32, mv: usize) -> u32 {
+        let permutation = self.apply_permutation(rank / ORIENTATIONS as u32, mv);
+        let orientation = self.apply_orientation(rank % ORIENTATIONS as u32, mv);
+        permutation * ORIENTATIONS as u32 + orientation
+    }
+
+    pub fn apply_permutation(&self, permutation: u32, mv: usize) -> u32 {
+        u32::from(self.permutation[permutation as usize][mv])
+    }
+
+    pub fn apply_orientation(&self, orientation: u32, mv: usize) -> u32 {
+        u32::from(self.orientation[orientation as usize][mv])
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::MoveTables;
+    use pieces::Pieces;
+    use solver::GENERATORS;
+
+    #[test]
+    fn test_tables_match_moving_pieces() {
+        let tables = MoveTables::new(&GENERATORS);
+        for &rank in &[0, 1, 728, 729, 1_234_567, 3_674_159] {
+            for (i, &mv) in GENERATORS.iter().enumerate() {
+                let mut pieces = Pieces::unrank(rank);
+                pieces.apply(&Pieces::effect_of(mv));
+                assert_eq!(tables.apply(rank, i), pieces.rank(), "{} {}", rank, mv);
+            }
+        }
+    }
+}
@@ -1,11 +1,18 @@
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+use algorithm::Algorithm;
+use facelets::{FaceletError, FaceletLetters};
+use moves::{Amount, Axis, Face, Move};
+use net::Net;
+use pieces::{identify_corner, slot_colors, Pieces};
+use validate::{self, InvalidCube};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Cube {
     blocks: [Block; 8],
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct Block {
     top: Color,
     bottom: Color,
@@ -15,14 +22,15 @@ pub struct Block {
     back: Color,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-enum Color {
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum Color {
     Y,
     P,
     B,
     R,
     G,
     W,
+    /// No colour at all, like the inside of a block.
     X,
 }
 
@@ -35,7 +43,7 @@ impl From<Color> for char {
             R => 'r',
             G => 'g',
             W => 'w',
-            X => panic!("Tried to print the insides of the cube :S"),
+            X => 'x',
         }
     }
 }
@@ -58,6 +66,36 @@ pub const BLOCKS: [Block; 8] = [Block {
 }; 8];
 
 impl Block {
+    pub fn new(
+        top: Color,
+        bottom: Color,
+        lhs: Color,
+        rhs: Color,
+        front: Color,
+        back: Color,
+    ) -> Self {
+        Block {
+            top,
+            bottom,
+            lhs,
+            rhs,
+            front,
+            back,
+        }
+    }
+
+    /// The colour facing towards `face`.
+    pub fn color(&self, face: Face) -> Color {
+        match face {
+            Face::U => self.top,
+            Face::D => self.bottom,
+            Face::L => self.lhs,
+            Face::R => self.rhs,
+            Face::F => self.front,
+            Face::B => self.back,
+        }
+    }
+
     pub fn tip_back(&mut self) -> Block {
         Block {
             bottom: self.back,
@@ -69,7 +107,7 @@ impl Block {
         }
     }
     pub fn tip_forwards(&mut self) -> Block {
-        return self.tip_back().tip_back().tip_back();
+        self.tip_back().tip_back().tip_back()
     }
     // rotates the cube to the right so that the lhs is now facing front.
     pub fn turn_right(&mut self) -> Block {
@@ -84,7 +122,55 @@ impl Block {
     }
 
     pub fn turn_left(&mut self) -> Block {
-        return self.turn_right().turn_right().turn_right();
+        self.turn_right().turn_right().turn_right()
+    }
+
+    // tips the block to the right so that the top is now facing rhs.
+    pub fn tip_right(&mut self) -> Block {
+        Block {
+            bottom: self.rhs,
+            front: self.front,
+            top: self.lhs,
+            back: self.back,
+            lhs: self.bottom,
+            rhs: self.top,
+        }
+    }
+
+    pub fn tip_left(&mut self) -> Block {
+        self.tip_right().tip_right().tip_right()
+    }
+}
+
+/// All 24 ways of holding the cube, each as a rotation that brings some
+/// face to the top followed by a turn about the vertical axis.
+pub fn rotations() -> Vec<Algorithm> {
+    let tips = [
+        None,
+        Some(Move::Rotate(Axis::X, Amount::Clockwise)),
+        Some(Move::Rotate(Axis::X, Amount::Half)),
+        Some(Move::Rotate(Axis::X, Amount::CounterClockwise)),
+        Some(Move::Rotate(Axis::Z, Amount::Clockwise)),
+        Some(Move::Rotate(Axis::Z, Amount::CounterClockwise)),
+    ];
+    let turns = [
+        None,
+        Some(Move::Rotate(Axis::Y, Amount::Clockwise)),
+        Some(Move::Rotate(Axis::Y, Amount::Half)),
+        Some(Move::Rotate(Axis::Y, Amount::CounterClockwise)),
+    ];
+    let mut all = Vec::with_capacity(24);
+    for &tip in &tips {
+        for &turn in &turns {
+            all.push(tip.into_iter().chain(turn).collect());
+        }
+    }
+    all
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Cube::new()
     }
 }
 
@@ -93,29 +179,94 @@ impl Cube {
         Cube { blocks: BLOCKS }
     }
 
+    pub fn from_blocks(blocks: [Block; 8]) -> Self {
+        Cube { blocks }
+    }
+
+    /// The block at `posn`. The top layer is 0 to 3 and the bottom
+    /// layer 4 to 7, each numbered back left, back right, front left,
+    /// front right.
+    pub fn block(&self, posn: usize) -> Block {
+        self.blocks[posn]
+    }
+
+    /// Reads a cube from a facelet string: four letters for each of the U,
+    /// R, F, D, L and B faces, each letter naming the face whose colour
+    /// it is. See `FaceletLetters` to use other letters.
+    pub fn from_facelets(facelets: &str) -> Result<Cube, FaceletError> {
+        FaceletLetters::default().read(facelets)
+    }
+
+    /// Writes the cube as a facelet string, as read by `from_facelets`.
+    pub fn to_facelets(&self) -> String {
+        FaceletLetters::default().write(self)
+    }
+
+    /// Shows every sticker of the cube unfolded flat, where `Display` only
+    /// shows the top, front and right.
+    pub fn net(&self) -> Net<'_> {
+        Net::new(self)
+    }
+
+    /// Checks that the cube could be solved, i.e. that it is made of the
+    /// eight corners of a real cube each sitting the right way round.
+    pub fn validate(&self) -> Result<Pieces, InvalidCube> {
+        validate::validate(self)
+    }
+
     pub fn is_solved(&self) -> bool {
-        let mut test = *self;
-        for _ in 0..4 {
-            for _ in 0..4 {
-                if test.blocks == BLOCKS {
-                    return true;
-                }
-                test.turn();
-            }
-            test.tip_back();
-        }
-        // ugly hack because otherwise some positions
-        // get left out; this could be more efficient:
-        for _ in 0..4 {
-            for _ in 0..4 {
-                if test.blocks == BLOCKS {
-                    return true;
-                }
-                test.tip_back();
-            }
-            test.turn();
+        self.canonical().blocks == BLOCKS
+    }
+
+    /// The same cube, held the one way out of 24 that every rotation of
+    /// it agrees on.
+    pub fn canonical(&self) -> Cube {
+        self.canonical_with_rotation().0
+    }
+
+    /// The canonical cube along with the rotation that turns this cube
+    /// into it.
+    ///
+    /// A cube that can be solved is held with its DBL corner in the DBL
+    /// slot, white side down, which leaves a solved cube as `Cube::new()`.
+    /// Cubes with no such corner, or several, fall back to whichever
+    /// rotation has the smallest blocks.
+    pub fn canonical_with_rotation(&self) -> (Cube, Algorithm) {
+        rotations()
+            .into_iter()
+            .map(|rotation| {
+                let mut rotated = *self;
+                rotation.apply(&mut rotated);
+                (rotated, rotation)
+            })
+            .min_by_key(|&(rotated, _)| {
+                let anchored = identify_corner(slot_colors(&rotated, 4)) == Some((4, 0));
+                (!anchored, rotated)
+            })
+            .expect("there is always a rotation")
+    }
+
+    /// Numbers the cube's state, ignoring how it is held, from 0 for
+    /// solved up to `pieces::STATES - 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cube can't be solved.
+    pub fn rank(&self) -> u32 {
+        match self.canonical().validate() {
+            Ok(pieces) => pieces.rank(),
+            Err(err) => panic!("can't rank an impossible cube: {}", err),
         }
-        return false;
+    }
+
+    /// The canonically held cube numbered `rank` by `Cube::rank`.
+    pub fn unrank(rank: u32) -> Cube {
+        Pieces::unrank(rank).to_cube()
+    }
+
+    /// Whether the two cubes are the same apart from how they are held.
+    pub fn eq_mod_rotation(&self, other: &Cube) -> bool {
+        self.canonical() == other.canonical()
     }
 
     /// Rotates the right-hand side of the cube towards the viewer.
@@ -210,8 +361,8 @@ impl Cube {
         }
         let mut next_blocks = self.blocks;
 
-        for posn in 0..8 {
-            next_blocks[posn] = self.blocks[preturn_posn(posn)].turn_left();
+        for (posn, block) in next_blocks.iter_mut().enumerate() {
+            *block = self.blocks[preturn_posn(posn)].turn_left();
         }
 
         self.blocks = next_blocks;
@@ -242,6 +393,69 @@ impl Cube {
         self.turn();
     }
 
+    /// Applies a single move in standard notation.
+    pub fn apply_move(&mut self, mv: Move) {
+        match mv {
+            Move::Turn(face, amount) => {
+                for _ in 0..amount.quarter_turns() {
+                    self.turn_face(face);
+                }
+            }
+            Move::Rotate(axis, amount) => {
+                for _ in 0..amount.quarter_turns() {
+                    self.rotate(axis);
+                }
+            }
+        }
+    }
+
+    /// Applies each of `moves` in order.
+    pub fn apply_moves(&mut self, moves: &[Move]) {
+        for &mv in moves {
+            self.apply_move(mv);
+        }
+    }
+
+    /// Turns `face` a quarter turn clockwise, looking at that face.
+    pub fn turn_face(&mut self, face: Face) {
+        match face {
+            Face::U => self.cycle([3, 2, 0, 1], Block::turn_left),
+            Face::D => self.cycle([6, 7, 5, 4], Block::turn_right),
+            Face::L => self.cycle([0, 2, 6, 4], Block::tip_forwards),
+            Face::R => self.cycle([3, 1, 5, 7], Block::tip_back),
+            Face::F => self.cycle([2, 3, 7, 6], Block::tip_right),
+            Face::B => self.cycle([1, 0, 4, 5], Block::tip_left),
+        }
+    }
+
+    /// Rotates the whole cube a quarter turn clockwise about `axis`.
+    pub fn rotate(&mut self, axis: Axis) {
+        match axis {
+            Axis::X => {
+                self.turn_face(Face::R);
+                self.cycle([4, 6, 2, 0], Block::tip_back);
+            }
+            Axis::Y => {
+                self.turn_face(Face::U);
+                self.cycle([7, 6, 4, 5], Block::turn_left);
+            }
+            Axis::Z => {
+                self.turn_face(Face::F);
+                self.cycle([0, 1, 5, 4], Block::tip_right);
+            }
+        }
+    }
+
+    /// Moves the block at each position of `posns` to the next one,
+    /// reorienting it with `reorient` on the way.
+    fn cycle(&mut self, posns: [usize; 4], reorient: fn(&mut Block) -> Block) {
+        let mut next_blocks = self.blocks;
+        for i in 0..4 {
+            next_blocks[posns[(i + 1) % 4]] = reorient(&mut self.blocks[posns[i]]);
+        }
+        self.blocks = next_blocks;
+    }
+
     /// Rotates the right-hand side of the cube towards the viewer `amount` times.
     fn twist_by(&mut self, amount: i8) {
         for _ in 0..amount {
@@ -304,7 +518,11 @@ impl Display for Cube {
 #[cfg(test)]
 mod test {
 
-    use super::Cube;
+    use super::{rotations, Cube};
+    use algorithm::cube_after;
+    use moves::{Move, AMOUNTS, AXES, FACES};
+    use pieces::{Pieces, STATES};
+    use std::collections::HashSet;
 
     #[test]
     fn test_new_cube_is_solved() {
@@ -638,6 +856,178 @@ mod test {
         assert_cube_strings_eq(&cube_a.to_string(), &cube_b.to_string());
     }
 
+    #[test]
+    fn test_every_rotation_of_solved_cube_is_canonically_solved() {
+        for rotation in rotations() {
+            let mut cube = Cube::new();
+            rotation.apply(&mut cube);
+            assert_eq!(cube.canonical(), Cube::new(), "{}", rotation);
+        }
+    }
+
+    #[test]
+    fn test_rotations_are_all_different() {
+        let mut cube = cube_after("R U F'");
+        let all: HashSet<Cube> = rotations()
+            .iter()
+            .map(|rotation| {
+                let mut rotated = cube;
+                rotation.apply(&mut rotated);
+                rotated
+            })
+            .collect();
+        assert_eq!(all.len(), 24);
+        cube.tip_right();
+        assert!(all.contains(&cube));
+    }
+
+    #[test]
+    fn test_canonical_is_the_same_for_every_rotation() {
+        let scrambled = cube_after("R U' F2 D L'");
+        for rotation in rotations() {
+            let mut cube = scrambled;
+            rotation.apply(&mut cube);
+            assert_eq!(cube.canonical(), scrambled.canonical(), "{}", rotation);
+            assert!(cube.eq_mod_rotation(&scrambled));
+        }
+        assert!(!scrambled.eq_mod_rotation(&cube_after("R U' F2 D L")));
+    }
+
+    #[test]
+    fn test_canonical_rotation_gets_to_canonical_cube() {
+        let cube = cube_after("y R U' z");
+        let (canonical, rotation) = cube.canonical_with_rotation();
+        let mut rotated = cube;
+        rotation.apply(&mut rotated);
+        assert_eq!(rotated, canonical);
+    }
+
+    #[test]
+    fn test_canonical_of_impossible_cube_is_the_same_for_every_rotation() {
+        let mut pieces = Pieces::solved();
+        pieces.permutation[4] = 7;
+        pieces.permutation[7] = 4;
+        pieces.orientation[0] = 1;
+        let broken = pieces.to_cube();
+        for rotation in rotations() {
+            let mut cube = broken;
+            rotation.apply(&mut cube);
+            assert_eq!(cube.canonical(), broken.canonical(), "{}", rotation);
+        }
+    }
+
+    #[test]
+    fn test_rank_ignores_rotation() {
+        let scrambled = cube_after("R U' F2 D L'");
+        for rotation in rotations() {
+            let mut cube = scrambled;
+            rotation.apply(&mut cube);
+            assert_eq!(cube.rank(), scrambled.rank(), "{}", rotation);
+        }
+        assert_eq!(cube_after("x y").rank(), 0);
+    }
+
+    #[test]
+    fn test_unrank_gives_back_the_same_state() {
+        for moves in &["", "R", "U F'", "R U R' U' F2 D L' B x"] {
+            let cube = cube_after(moves);
+            let rank = cube.rank();
+            assert!(rank < STATES);
+            assert!(Cube::unrank(rank).eq_mod_rotation(&cube), "{}", moves);
+            assert_eq!(Cube::unrank(rank).rank(), rank);
+        }
+    }
+
+    #[test]
+    fn test_different_states_rank_differently() {
+        let ranks: HashSet<u32> = ["", "R", "R2", "R'", "U", "F", "R U", "U R"]
+            .iter()
+            .map(|moves| cube_after(moves).rank())
+            .collect();
+        assert_eq!(ranks.len(), 8);
+    }
+
+    #[test]
+    fn test_moves_match_existing_twists() {
+        let mut cube = Cube::new();
+        cube.twist();
+        assert_eq!(cube_after("R'"), cube);
+
+        let mut cube = Cube::new();
+        cube.front_twist();
+        assert_eq!(cube_after("F"), cube);
+
+        let mut cube = Cube::new();
+        cube.bottom_twist();
+        assert_eq!(cube_after("D"), cube);
+    }
+
+    #[test]
+    fn test_rotations_match_existing_tips_and_turns() {
+        let mut cube = cube_after("R U'");
+        cube.tip_back();
+        assert_eq!(cube_after("R U' x"), cube);
+
+        let mut cube = cube_after("R U'");
+        cube.turn();
+        assert_eq!(cube_after("R U' y"), cube);
+
+        let mut cube = cube_after("R U'");
+        cube.tip_right();
+        assert_eq!(cube_after("R U' z"), cube);
+    }
+
+    #[test]
+    fn test_every_move_four_times_is_identity() {
+        for &face in &FACES {
+            let mut cube = cube_after("R U F");
+            for _ in 0..4 {
+                cube.apply_move(Move::Turn(face, AMOUNTS[0]));
+            }
+            assert_eq!(cube, cube_after("R U F"), "{:?}", face);
+        }
+        for &axis in &AXES {
+            let mut cube = cube_after("R U F");
+            for _ in 0..4 {
+                cube.apply_move(Move::Rotate(axis, AMOUNTS[0]));
+            }
+            assert_eq!(cube, cube_after("R U F"), "{:?}", axis);
+        }
+    }
+
+    #[test]
+    fn test_move_then_inverse_is_identity() {
+        for &face in &FACES {
+            for &amount in &AMOUNTS {
+                let mv = Move::Turn(face, amount);
+                let mut cube = cube_after("L D B");
+                cube.apply_move(mv);
+                cube.apply_move(mv.inverse());
+                assert_eq!(cube, cube_after("L D B"), "{}", mv);
+            }
+        }
+    }
+
+    #[test]
+    fn test_opposite_faces_are_the_same_up_to_rotation() {
+        assert_eq!(cube_after("L x"), cube_after("R"));
+        assert_eq!(cube_after("D y"), cube_after("U"));
+        assert_eq!(cube_after("B z"), cube_after("F"));
+    }
+
+    #[test]
+    fn test_sexy_move_six_times_is_identity() {
+        let cube = cube_after(&"R U R' U' ".repeat(6));
+        assert_eq!(cube, Cube::new());
+    }
+
+    #[test]
+    fn test_every_face_turn_unsolves() {
+        for face in &["U", "D", "L", "R", "F", "B", "U2", "B'"] {
+            assert!(!cube_after(face).is_solved(), "{}", face);
+        }
+    }
+
     fn assert_cube_strings_eq(expected: &str, actual: &str) {
         assert!(
             actual == expected,
@@ -0,0 +1,611 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use cases::{self, is_one_color, is_solved_as_held, layer_turns, turns, Case, Recognition};
+use cll::{self, CLL_CASES};
+use cube::Cube;
+use facelets::face_colors;
+use moves::Face;
+
+/// Every EG-1 case: the top corners in any CLL case other than the two
+/// where they all face up, with two neighbouring bottom corners swapped.
+/// Each is named after its CLL case, and held the same way on top.
+///
+/// The descriptions read the top as `CLL_CASES` does, then say which side
+/// of the bottom layer has two matching stickers.
+pub const EG1_CASES: [Case; 40] = [
+    Case {
+        name: "EG-1 Sune 1",
+        description: "opposite colours on the left; the sides read aU aU cU db; headlights on the back of the bottom",
+        notation: "F U2 F U' F2 R U2 R'",
+    },
+    Case {
+        name: "EG-1 Sune 2",
+        description: "neighbouring colours on the left; the sides read aU aU dU cb; headlights on the right of the bottom",
+        notation: "R' U F2 R2 U F'",
+    },
+    Case {
+        name: "EG-1 Sune 3",
+        description: "opposite colours on the left; the sides read aU cU aU db; headlights on the left of the bottom",
+        notation: "R2 U' F U' F U' R F2",
+    },
+    Case {
+        name: "EG-1 Sune 4",
+        description: "neighbouring colours on the left; the sides read aU cU dU ab; headlights on the back of the bottom",
+        notation: "F U F2 R U2 R2 F' R",
+    },
+    Case {
+        name: "EG-1 Sune 5",
+        description: "neighbouring colours on the left; the sides read aU dU aU cb; headlights on the front of the bottom",
+        notation: "R' U2 R F2 U' R F2 R",
+    },
+    Case {
+        name: "EG-1 Sune 6",
+        description: "neighbouring colours on the left; the sides read aU dU cU ab; headlights on the back of the bottom",
+        notation: "R F2 R U2 R U' F2 U F'",
+    },
+    Case {
+        name: "EG-1 Antisune 1",
+        description: "opposite colours on the back; the sides read Ua Ua db Uc; headlights on the front of the bottom",
+        notation: "F2 U R F' U2 R2 U' F'",
+    },
+    Case {
+        name: "EG-1 Antisune 2",
+        description: "neighbouring colours on the back; the sides read Ua Ua dc Ub; headlights on the front of the bottom",
+        notation: "F U' R2 F2 U' R",
+    },
+    Case {
+        name: "EG-1 Antisune 3",
+        description: "neighbouring colours on the back; the sides read Ua Uc bc Ud; headlights on the right of the bottom",
+        notation: "F2 R2 F' R' U2 F2 R' F",
+    },
+    Case {
+        name: "EG-1 Antisune 4",
+        description: "opposite colours on the back; the sides read Ua Uc bd Uc; headlights on the front of the bottom",
+        notation: "R2 F' R F' U R' U R2",
+    },
+    Case {
+        name: "EG-1 Antisune 5",
+        description: "neighbouring colours on the back; the sides read Ua Ud cb Ud; headlights on the right of the bottom",
+        notation: "F U2 F' R2 U F' R2 F'",
+    },
+    Case {
+        name: "EG-1 Antisune 6",
+        description: "neighbouring colours on the back; the sides read Ua Ud cd Ub; headlights on the right of the bottom",
+        notation: "F2 R2 U F' U R' F R F2",
+    },
+    Case {
+        name: "EG-1 H 1",
+        description: "headlights on the right, headlights on the left; the sides read UU aa UU cc; headlights on the front of the bottom",
+        notation: "F R' U R2 U2 F' R F R'",
+    },
+    Case {
+        name: "EG-1 H 2",
+        description: "headlights on the right, opposite colours on the left; the sides read UU aa UU db; headlights on the right of the bottom",
+        notation: "R F2 R F' R F U' F",
+    },
+    Case {
+        name: "EG-1 H 3",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read UU ab UU bc; headlights on the left of the bottom",
+        notation: "R F' R F2 U' R' F U' F2",
+    },
+    Case {
+        name: "EG-1 H 4",
+        description: "opposite colours on the right, opposite colours on the left; the sides read UU ac UU ca; headlights on the left of the bottom",
+        notation: "R F2 R F' R' F U' F",
+    },
+    Case {
+        name: "EG-1 Pi 1",
+        description: "headlights on the right; the sides read aU bb Uc UU; headlights on the right of the bottom",
+        notation: "F U F2 R' U2 F R'",
+    },
+    Case {
+        name: "EG-1 Pi 2",
+        description: "neighbouring colours on the right; the sides read aU bc Ub UU; headlights on the back of the bottom",
+        notation: "R U2 F2 R F' R2 U' F' R'",
+    },
+    Case {
+        name: "EG-1 Pi 3",
+        description: "opposite colours on the right; the sides read aU ca Uc UU; headlights on the right of the bottom",
+        notation: "R U' R U' F2 R F R",
+    },
+    Case {
+        name: "EG-1 Pi 4",
+        description: "headlights on the right; the sides read aU cc Ua UU; headlights on the front of the bottom",
+        notation: "F U' F R U' R F2 R",
+    },
+    Case {
+        name: "EG-1 Pi 5",
+        description: "neighbouring colours on the right; the sides read aU da Ub UU; headlights on the back of the bottom",
+        notation: "F' U F R' F U2 F2 R' F",
+    },
+    Case {
+        name: "EG-1 Pi 6",
+        description: "opposite colours on the right; the sides read aU db Ua UU; headlights on the right of the bottom",
+        notation: "R U R2 U F2 R2 U' F'",
+    },
+    Case {
+        name: "EG-1 U 1",
+        description: "neighbouring colours on the front, opposite colours on the right, neighbouring colours on the back; the sides read ab ac bc UU; headlights on the left of the bottom",
+        notation: "R2 U' R' U2 R' U2 R'",
+    },
+    Case {
+        name: "EG-1 U 2",
+        description: "neighbouring colours on the front, neighbouring colours on the right, neighbouring colours on the back; the sides read ab ad cb UU; headlights on the right of the bottom",
+        notation: "F U2 R2 F' R2 F U F2",
+    },
+    Case {
+        name: "EG-1 U 3",
+        description: "opposite colours on the front, headlights on the right, opposite colours on the back; the sides read ac bb ac UU; headlights on the left of the bottom",
+        notation: "R U2 F' U2 F U2 R'",
+    },
+    Case {
+        name: "EG-1 U 4",
+        description: "opposite colours on the front, opposite colours on the right, opposite colours on the back; the sides read ac bd ca UU; headlights on the right of the bottom",
+        notation: "F R' F R' U' F2 R U2 R",
+    },
+    Case {
+        name: "EG-1 U 5",
+        description: "neighbouring colours on the front, neighbouring colours on the right, neighbouring colours on the back; the sides read ad cb ab UU; headlights on the front of the bottom",
+        notation: "F2 U' R F2 R F U F'",
+    },
+    Case {
+        name: "EG-1 U 6",
+        description: "neighbouring colours on the front, headlights on the right, neighbouring colours on the back; the sides read ad cc ba UU; headlights on the back of the bottom",
+        notation: "F R2 F U2 F U F2 R2",
+    },
+    Case {
+        name: "EG-1 T 1",
+        description: "opposite colours on the right, headlights on the left; the sides read Ua db aU cc; headlights on the front of the bottom",
+        notation: "R F' U2 R U' F2 U F",
+    },
+    Case {
+        name: "EG-1 T 2",
+        description: "opposite colours on the right, opposite colours on the left; the sides read Ua db aU db; headlights on the front of the bottom",
+        notation: "R F U2 R2 U' F' U R F2",
+    },
+    Case {
+        name: "EG-1 T 3",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read Ua dc bU bc; headlights on the right of the bottom",
+        notation: "F2 R' U2 R' U F2 U2 F",
+    },
+    Case {
+        name: "EG-1 T 4",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read Ua dc bU da; headlights on the back of the bottom",
+        notation: "F' R F R2 F R U2 R",
+    },
+    Case {
+        name: "EG-1 T 5",
+        description: "headlights on the right, headlights on the left; the sides read Ua dd cU bb; headlights on the right of the bottom",
+        notation: "F' R U F2 R' U' F2 U F'",
+    },
+    Case {
+        name: "EG-1 T 6",
+        description: "headlights on the right, opposite colours on the left; the sides read Ua dd cU ca; headlights on the right of the bottom",
+        notation: "F2 R2 U F U2 F U2 F",
+    },
+    Case {
+        name: "EG-1 L 1",
+        description: "neighbouring colours on the back, neighbouring colours on the left; the sides read Ua dU bc bc; headlights on the back of the bottom",
+        notation: "F U2 F' U F2 U' F U' F",
+    },
+    Case {
+        name: "EG-1 L 2",
+        description: "opposite colours on the back, neighbouring colours on the left; the sides read Ua dU bd cb; headlights on the right of the bottom",
+        notation: "F' R' F U2 F R F' R2",
+    },
+    Case {
+        name: "EG-1 L 3",
+        description: "neighbouring colours on the back, opposite colours on the left; the sides read Ua dU cb ac; headlights on the front of the bottom",
+        notation: "R' F2 R' F' R2 F' U' R",
+    },
+    Case {
+        name: "EG-1 L 4",
+        description: "neighbouring colours on the back, opposite colours on the left; the sides read Ua dU cd ca; headlights on the left of the bottom",
+        notation: "R U' F2 U F R' U2 F",
+    },
+    Case {
+        name: "EG-1 L 5",
+        description: "opposite colours on the back, neighbouring colours on the left; the sides read Ua dU db ab; headlights on the front of the bottom",
+        notation: "F R2 F R' F2 R F R'",
+    },
+    Case {
+        name: "EG-1 L 6",
+        description: "neighbouring colours on the back, neighbouring colours on the left; the sides read Ua dU dc ba; headlights on the left of the bottom",
+        notation: "F R' F2 U' F R F' U2 F",
+    },
+];
+
+/// Every EG-2 case: the top corners in any CLL case other than the two
+/// where they all face up, with diagonally opposite bottom corners
+/// swapped, so that no side of the bottom layer matches.
+pub const EG2_CASES: [Case; 40] = [
+    Case {
+        name: "EG-2 Sune 1",
+        description: "opposite colours on the left; the sides read aU aU cU db",
+        notation: "R2 F U2 R' U' F R F",
+    },
+    Case {
+        name: "EG-2 Sune 2",
+        description: "neighbouring colours on the left; the sides read aU aU dU cb",
+        notation: "R F2 U' F U R2 U F U F",
+    },
+    Case {
+        name: "EG-2 Sune 3",
+        description: "opposite colours on the left; the sides read aU cU aU db",
+        notation: "F' R' U R' U2 F R' U R'",
+    },
+    Case {
+        name: "EG-2 Sune 4",
+        description: "neighbouring colours on the left; the sides read aU cU dU ab",
+        notation: "R U R' U R U2 R F2 R2",
+    },
+    Case {
+        name: "EG-2 Sune 5",
+        description: "neighbouring colours on the left; the sides read aU dU aU cb",
+        notation: "R F R U' F U2 R' F2",
+    },
+    Case {
+        name: "EG-2 Sune 6",
+        description: "neighbouring colours on the left; the sides read aU dU cU ab",
+        notation: "F R' F R' F2 R' U2 R",
+    },
+    Case {
+        name: "EG-2 Antisune 1",
+        description: "opposite colours on the back; the sides read Ua Ua db Uc",
+        notation: "F2 R U2 F' U F' U' R'",
+    },
+    Case {
+        name: "EG-2 Antisune 2",
+        description: "neighbouring colours on the back; the sides read Ua Ua dc Ub",
+        notation: "R F R' U2 F2 U' R2 U' F R2",
+    },
+    Case {
+        name: "EG-2 Antisune 3",
+        description: "neighbouring colours on the back; the sides read Ua Uc bc Ud",
+        notation: "F2 U' R U2 R2 F' R' F2 R'",
+    },
+    Case {
+        name: "EG-2 Antisune 4",
+        description: "opposite colours on the back; the sides read Ua Uc bd Uc",
+        notation: "F U' F R' F2 U R' F R",
+    },
+    Case {
+        name: "EG-2 Antisune 5",
+        description: "neighbouring colours on the back; the sides read Ua Ud cb Ud",
+        notation: "F' R' F' U R U2 F' R2",
+    },
+    Case {
+        name: "EG-2 Antisune 6",
+        description: "neighbouring colours on the back; the sides read Ua Ud cd Ub",
+        notation: "F' R2 F U2 F R' U R'",
+    },
+    Case {
+        name: "EG-2 H 1",
+        description: "headlights on the right, headlights on the left; the sides read UU aa UU cc",
+        notation: "R2 U2 R U2 F2 R2",
+    },
+    Case {
+        name: "EG-2 H 2",
+        description: "headlights on the right, opposite colours on the left; the sides read UU aa UU db",
+        notation: "R F2 U2 R' U F R' F R'",
+    },
+    Case {
+        name: "EG-2 H 3",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read UU ab UU bc",
+        notation: "R F2 R U' F2 U' F2 R' F",
+    },
+    Case {
+        name: "EG-2 H 4",
+        description: "opposite colours on the right, opposite colours on the left; the sides read UU ac UU ca",
+        notation: "F2 R F2 R2 U2 R' F2",
+    },
+    Case {
+        name: "EG-2 Pi 1",
+        description: "headlights on the right; the sides read aU bb Uc UU",
+        notation: "R U2 R2 U R F2 R2 F",
+    },
+    Case {
+        name: "EG-2 Pi 2",
+        description: "neighbouring colours on the right; the sides read aU bc Ub UU",
+        notation: "F U2 F R2 F2 U' F R' F'",
+    },
+    Case {
+        name: "EG-2 Pi 3",
+        description: "opposite colours on the right; the sides read aU ca Uc UU",
+        notation: "R F' R2 U' R2 U' F R2 F",
+    },
+    Case {
+        name: "EG-2 Pi 4",
+        description: "headlights on the right; the sides read aU cc Ua UU",
+        notation: "F R' U F' U' F R2 U2 F'",
+    },
+    Case {
+        name: "EG-2 Pi 5",
+        description: "neighbouring colours on the right; the sides read aU da Ub UU",
+        notation: "F U F R2 F2 U' F R2 F'",
+    },
+    Case {
+        name: "EG-2 Pi 6",
+        description: "opposite colours on the right; the sides read aU db Ua UU",
+        notation: "R2 U F2 R' U2 R' F2 U F2",
+    },
+    Case {
+        name: "EG-2 U 1",
+        description: "neighbouring colours on the front, opposite colours on the right, neighbouring colours on the back; the sides read ab ac bc UU",
+        notation: "R F' U R U R2 U2 R U F2",
+    },
+    Case {
+        name: "EG-2 U 2",
+        description: "neighbouring colours on the front, neighbouring colours on the right, neighbouring colours on the back; the sides read ab ad cb UU",
+        notation: "F R2 U' F' R' U F' U R",
+    },
+    Case {
+        name: "EG-2 U 3",
+        description: "opposite colours on the front, headlights on the right, opposite colours on the back; the sides read ac bb ac UU",
+        notation: "R U R F2 R2 U2 R U' R'",
+    },
+    Case {
+        name: "EG-2 U 4",
+        description: "opposite colours on the front, opposite colours on the right, opposite colours on the back; the sides read ac bd ca UU",
+        notation: "R F R' F U' R F2 R2 F R'",
+    },
+    Case {
+        name: "EG-2 U 5",
+        description: "neighbouring colours on the front, neighbouring colours on the right, neighbouring colours on the back; the sides read ad cb ab UU",
+        notation: "F' U2 R U R F' R F' R'",
+    },
+    Case {
+        name: "EG-2 U 6",
+        description: "neighbouring colours on the front, headlights on the right, neighbouring colours on the back; the sides read ad cc ba UU",
+        notation: "F R U R' U' F R2 F2",
+    },
+    Case {
+        name: "EG-2 T 1",
+        description: "opposite colours on the right, headlights on the left; the sides read Ua db aU cc",
+        notation: "R F' U F' U R' U' R2",
+    },
+    Case {
+        name: "EG-2 T 2",
+        description: "opposite colours on the right, opposite colours on the left; the sides read Ua db aU db",
+        notation: "R F U' F U2 F' R U R2 F2",
+    },
+    Case {
+        name: "EG-2 T 3",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read Ua dc bU bc",
+        notation: "F' R F2 R F U' R2 F",
+    },
+    Case {
+        name: "EG-2 T 4",
+        description: "neighbouring colours on the right, neighbouring colours on the left; the sides read Ua dc bU da",
+        notation: "F U' R2 U' R' U R2 F'",
+    },
+    Case {
+        name: "EG-2 T 5",
+        description: "headlights on the right, headlights on the left; the sides read Ua dd cU bb",
+        notation: "R F2 R U2 R' F2 R'",
+    },
+    Case {
+        name: "EG-2 T 6",
+        description: "headlights on the right, opposite colours on the left; the sides read Ua dd cU ca",
+        notation: "R2 F2 R F' U' F U R",
+    },
+    Case {
+        name: "EG-2 L 1",
+        description: "neighbouring colours on the back, neighbouring colours on the left; the sides read Ua dU bc bc",
+        notation: "F U2 R2 F U2 F2 R F' R",
+    },
+    Case {
+        name: "EG-2 L 2",
+        description: "opposite colours on the back, neighbouring colours on the left; the sides read Ua dU bd cb",
+        notation: "R' U2 F U' F' U2 F' R",
+    },
+    Case {
+        name: "EG-2 L 3",
+        description: "neighbouring colours on the back, opposite colours on the left; the sides read Ua dU cb ac",
+        notation: "F U2 R' U R U2 R F'",
+    },
+    Case {
+        name: "EG-2 L 4",
+        description: "neighbouring colours on the back, opposite colours on the left; the sides read Ua dU cd ca",
+        notation: "F R2 U R' U' R2 F R2 F2",
+    },
+    Case {
+        name: "EG-2 L 5",
+        description: "opposite colours on the back, neighbouring colours on the left; the sides read Ua dU db ab",
+        notation: "F R' U2 F U R F' U R2",
+    },
+    Case {
+        name: "EG-2 L 6",
+        description: "neighbouring colours on the back, neighbouring colours on the left; the sides read Ua dU dc ba",
+        notation: "R F R' U R' U2 F U' R' F",
+    },
+];
+
+/// The sets of cases the EG method uses for the last step, told apart by
+/// how the bottom corners are arranged once the first face is made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EgSet {
+    /// The bottom layer is solved.
+    Cll,
+    /// Two neighbouring bottom corners are swapped.
+    Eg1,
+    /// Two diagonally opposite bottom corners are swapped.
+    Eg2,
+}
+
+pub const EG_SETS: [EgSet; 3] = [EgSet::Cll, EgSet::Eg1, EgSet::Eg2];
+
+impl EgSet {
+    pub fn cases(self) -> &'static [Case] {
+        match self {
+            EgSet::Cll => &CLL_CASES,
+            EgSet::Eg1 => &EG1_CASES,
+            EgSet::Eg2 => &EG2_CASES,
+        }
+    }
+}
+
+impl Display for EgSet {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let name = match *self {
+            EgSet::Cll => "CLL",
+            EgSet::Eg1 => "EG-1",
+            EgSet::Eg2 => "EG-2",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Which set `cube` is in, going by how many sides of its bottom layer
+/// match: all four for CLL, one for EG-1 and none for EG-2. Returns `None`
+/// if the bottom face isn't one colour.
+pub fn bottom_set(cube: &Cube) -> Option<EgSet> {
+    if !is_one_color(cube, Face::D) {
+        return None;
+    }
+    // The sides are read with U at the top, so the bottom row comes last.
+    let matching = [Face::F, Face::R, Face::B, Face::L]
+        .iter()
+        .filter(|&&side| {
+            let colors = face_colors(cube, side);
+            colors[2] == colors[3]
+        })
+        .count();
+    match matching {
+        4 => Some(EgSet::Cll),
+        1 => Some(EgSet::Eg1),
+        _ => Some(EgSet::Eg2),
+    }
+}
+
+/// Works out which set and case `cube` is in, whatever colour its first
+/// face is. The recognition's setup turns U and D to hold the case as its
+/// algorithm expects, and its finish is the post-AUF.
+///
+/// Returns `None` if the first face isn't on the bottom, if the top
+/// corners all face up while the bottom is swapped (a PBL case), or if the
+/// cube needs nothing more than a turn of U.
+pub fn recognize(cube: &Cube) -> Option<(EgSet, Recognition<'static>)> {
+    let set = bottom_set(cube)?;
+    let recognition = match set {
+        EgSet::Cll => cll::recognize(cube),
+        _ => cases::recognize(
+            cube,
+            set.cases(),
+            &layer_turns(),
+            &turns(Face::U),
+            is_solved_as_held,
+        ),
+    }?;
+    Some((set, recognition))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{bottom_set, recognize, EgSet, EG_SETS};
+    use algorithm::cube_after;
+    use cases::{is_solved_as_held, layer_turns, turns};
+    use cll::side_letters;
+    use cube::Cube;
+    use facelets::face_colors;
+    use moves::Face;
+
+    #[test]
+    fn test_descriptions_match_the_cases() {
+        let sides = [
+            (Face::F, "front"),
+            (Face::R, "right"),
+            (Face::B, "back"),
+            (Face::L, "left"),
+        ];
+        for &set in &EG_SETS {
+            for case in set.cases() {
+                let mut cube = Cube::new();
+                case.algorithm().inverse().apply(&mut cube);
+                let mut parts = case
+                    .description
+                    .split("the sides read ")
+                    .nth(1)
+                    .unwrap()
+                    .split("; ");
+                assert_eq!(
+                    parts.next(),
+                    Some(side_letters(&cube).as_str()),
+                    "{}",
+                    case.name
+                );
+                // The bottom row of a side comes after its top row.
+                let headlights = sides.iter().find(|&&(side, _)| {
+                    let colors = face_colors(&cube, side);
+                    colors[2] == colors[3]
+                });
+                let bottom = parts.next();
+                match set {
+                    EgSet::Eg1 => assert_eq!(
+                        bottom,
+                        headlights
+                            .map(|&(_, name)| format!("headlights on the {} of the bottom", name))
+                            .as_deref(),
+                        "{}",
+                        case.name
+                    ),
+                    _ => assert_eq!(bottom, None, "{}", case.name),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_case_is_recognised() {
+        let setups = layer_turns();
+        let finishes = turns(Face::U);
+        for &set in &[EgSet::Eg1, EgSet::Eg2] {
+            for (i, case) in set.cases().iter().enumerate() {
+                assert!(set.cases()[..i].iter().all(|other| other.name != case.name));
+                let mut cube = Cube::new();
+                setups[i % setups.len()]
+                    .then(&case.algorithm())
+                    .then(&finishes[i % finishes.len()])
+                    .inverse()
+                    .apply(&mut cube);
+                assert_eq!(bottom_set(&cube), Some(set), "{}", case.name);
+                let (found, recognition) = recognize(&cube).unwrap();
+                assert_eq!(found, set);
+                assert_eq!(recognition.case.name, case.name);
+                recognition.algorithm().apply(&mut cube);
+                assert!(is_solved_as_held(&cube), "{}", case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_any_colour_on_the_bottom() {
+        for (rotation, &set) in ["z", "x'", "y2"].iter().zip(EG_SETS.iter()) {
+            let case = &set.cases()[7];
+            let mut cube = cube_after(rotation);
+            case.algorithm().inverse().apply(&mut cube);
+            let (found, recognition) = recognize(&cube).unwrap();
+            assert_eq!(found, set);
+            assert_eq!(recognition.case.name, case.name);
+        }
+    }
+
+    #[test]
+    fn test_bottom_set() {
+        assert_eq!(bottom_set(&Cube::new()), Some(EgSet::Cll));
+        assert_eq!(bottom_set(&cube_after("R")), None);
+        // A T-perm and a Y-perm done upside down.
+        let adjacent = cube_after("x2 R U R' U' R' F R2 U' R' U' R U R' F' x2");
+        assert_eq!(bottom_set(&adjacent), Some(EgSet::Eg1));
+        assert_eq!(
+            bottom_set(&cube_after(
+                "x2 F R U' R' U' R U R' F' R U R' U' R' F R F' x2"
+            )),
+            Some(EgSet::Eg2)
+        );
+        // Every top corner faces up, so this is a PBL case, not EG-1.
+        assert_eq!(recognize(&adjacent), None);
+        assert_eq!(recognize(&cube_after("U")), None);
+    }
+}
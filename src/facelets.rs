@@ -0,0 +1,261 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use cube::{Block, Color, Cube, BLOCKS};
+use moves::Face;
+use moves::Face::*;
+use validate::InvalidCube;
+
+/// Faces in the order they appear in a facelet string.
+pub const FACE_ORDER: [Face; 6] = [U, R, F, D, L, B];
+
+/// The slot behind each sticker of a face, in the order the face is read:
+/// top row then bottom row, looking straight at the face with U on top
+/// (or, for U and D, with B and F on top respectively).
+const FACELET_SLOTS: [[usize; 4]; 6] = [
+    [0, 1, 2, 3],
+    [3, 1, 7, 5],
+    [2, 3, 6, 7],
+    [6, 7, 4, 5],
+    [0, 2, 4, 6],
+    [1, 0, 5, 4],
+];
+
+/// Which letter stands for which colour in a facelet string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FaceletLetters {
+    /// The letter for the colour belonging on each face of `FACE_ORDER`.
+    letters: [char; 6],
+}
+
+/// Why a facelet string couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FaceletError {
+    /// There should be 24 letters, not counting whitespace.
+    WrongLength(usize),
+    /// The letter at byte offset `position` doesn't stand for any colour.
+    UnknownLetter { position: usize, letter: char },
+    /// A letter mapping needs six different letters.
+    BadLetters(String),
+    /// The letters are fine but describe a cube that can't be solved.
+    Invalid(InvalidCube),
+}
+
+impl Display for FaceletError {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        match *self {
+            FaceletError::WrongLength(len) => {
+                write!(formatter, "expected 24 facelets but got {}", len)
+            }
+            FaceletError::UnknownLetter { position, letter } => write!(
+                formatter,
+                "`{}` at position {} isn't one of the facelet letters",
+                letter, position
+            ),
+            FaceletError::BadLetters(ref letters) => write!(
+                formatter,
+                "`{}` should be six different letters, one per face",
+                letters
+            ),
+            FaceletError::Invalid(ref err) => write!(formatter, "{}", err),
+        }
+    }
+}
+
+impl Error for FaceletError {}
+
+impl From<InvalidCube> for FaceletError {
+    fn from(err: InvalidCube) -> Self {
+        FaceletError::Invalid(err)
+    }
+}
+
+impl Default for FaceletLetters {
+    /// Each colour written as the face it belongs on, e.g. `U` for yellow.
+    fn default() -> Self {
+        FaceletLetters {
+            letters: ['U', 'R', 'F', 'D', 'L', 'B'],
+        }
+    }
+}
+
+impl FaceletLetters {
+    /// Uses the letters of `letters` for the colours belonging on the U,
+    /// R, F, D, L and B faces, in that order. `"ybrwgp"` matches how the
+    /// cube is printed.
+    pub fn new(letters: &str) -> Result<Self, FaceletError> {
+        let chars: Vec<char> = letters.chars().collect();
+        let distinct = chars
+            .iter()
+            .enumerate()
+            .all(|(i, c)| !chars[..i].contains(c));
+        if chars.len() != 6 || !distinct {
+            return Err(FaceletError::BadLetters(letters.to_string()));
+        }
+        let mut mapping = FaceletLetters::default();
+        mapping.letters.copy_from_slice(&chars);
+        Ok(mapping)
+    }
+
+    fn letter(&self, color: Color) -> char {
+        FACE_ORDER
+            .iter()
+            .position(|&face| BLOCKS[0].color(face) == color)
+            .map_or('?', |i| self.letters[i])
+    }
+
+    fn color(&self, letter: char) -> Option<Color> {
+        self.letters
+            .iter()
+            .position(|&c| c == letter)
+            .map(|i| BLOCKS[0].color(FACE_ORDER[i]))
+    }
+
+    /// Reads a cube from 24 letters, four for each face in `FACE_ORDER`.
+    /// Whitespace is ignored, so the faces can be spaced out.
+    pub fn read(&self, facelets: &str) -> Result<Cube, FaceletError> {
+        let mut colors = Vec::with_capacity(24);
+        for (position, letter) in facelets.char_indices() {
+            if letter.is_whitespace() {
+                continue;
+            }
+            match self.color(letter) {
+                Some(color) => colors.push(color),
+                None => return Err(FaceletError::UnknownLetter { position, letter }),
+            }
+        }
+        if colors.len() != 24 {
+            return Err(FaceletError::WrongLength(colors.len()));
+        }
+
+        // Stick the colours on blank blocks so `validate` can say what,
+        // if anything, is wrong with them.
+        let mut stickers = [[Color::X; 6]; 8];
+        for (f, slots) in FACELET_SLOTS.iter().enumerate() {
+            for (i, &slot) in slots.iter().enumerate() {
+                stickers[slot][face_index(FACE_ORDER[f])] = colors[f * 4 + i];
+            }
+        }
+        let mut blocks = BLOCKS;
+        for (block, s) in blocks.iter_mut().zip(stickers.iter()) {
+            *block = Block::new(s[0], s[3], s[4], s[1], s[2], s[5]);
+        }
+        let pieces = Cube::from_blocks(blocks).validate()?;
+        Ok(pieces.to_cube())
+    }
+
+    /// Writes `cube` as 24 letters, four for each face in `FACE_ORDER`.
+    pub fn write(&self, cube: &Cube) -> String {
+        FACE_ORDER
+            .iter()
+            .flat_map(|&face| face_colors(cube, face).to_vec())
+            .map(|color| self.letter(color))
+            .collect()
+    }
+}
+
+/// The four stickers of `face`, top row then bottom row, as they appear
+/// looking straight at it. The U face is seen with B at the top and the D
+/// face with F at the top.
+pub fn face_colors(cube: &Cube, face: Face) -> [Color; 4] {
+    let slots = FACELET_SLOTS[face_index(face)];
+    [
+        cube.block(slots[0]).color(face),
+        cube.block(slots[1]).color(face),
+        cube.block(slots[2]).color(face),
+        cube.block(slots[3]).color(face),
+    ]
+}
+
+/// Where `face` comes in `FACE_ORDER`.
+pub fn face_index(face: Face) -> usize {
+    FACE_ORDER
+        .iter()
+        .position(|&f| f == face)
+        .expect("every face is in FACE_ORDER")
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{FaceletError, FaceletLetters};
+    use algorithm::cube_after;
+    use cube::{Color, Cube};
+    use validate::InvalidCube;
+
+    #[test]
+    fn test_solved_cube() {
+        assert_eq!(Cube::new().to_facelets(), "UUUURRRRFFFFDDDDLLLLBBBB");
+        assert_eq!(
+            Cube::from_facelets("UUUURRRRFFFFDDDDLLLLBBBB"),
+            Ok(Cube::new())
+        );
+    }
+
+    #[test]
+    fn test_u_turn() {
+        assert_eq!(cube_after("U").to_facelets(), "UUUUBBRRRRFFDDDDFFLLLLBB");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for moves in &["R", "R U F'", "R U R' U' F2 D L' B", "x R y2 U z'"] {
+            let cube = cube_after(moves);
+            assert_eq!(Cube::from_facelets(&cube.to_facelets()), Ok(cube));
+        }
+    }
+
+    #[test]
+    fn test_whitespace_is_ignored() {
+        assert_eq!(
+            Cube::from_facelets("UUUU RRRR FFFF\nDDDD LLLL BBBB"),
+            Ok(Cube::new())
+        );
+    }
+
+    #[test]
+    fn test_custom_letters() {
+        let letters = FaceletLetters::new("ybrwgp").unwrap();
+        let cube = cube_after("R U2 F'");
+        let facelets = letters.write(&cube);
+
+        assert!(facelets.chars().all(|c| "ybrwgp".contains(c)));
+        assert_eq!(letters.read(&facelets), Ok(cube));
+        assert_eq!(letters.write(&Cube::new()), "yyyybbbbrrrrwwwwggggpppp");
+    }
+
+    #[test]
+    fn test_bad_letter_mappings() {
+        assert_eq!(
+            FaceletLetters::new("ybrwg"),
+            Err(FaceletError::BadLetters("ybrwg".to_string()))
+        );
+        assert_eq!(
+            FaceletLetters::new("yyrwgp"),
+            Err(FaceletError::BadLetters("yyrwgp".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Cube::from_facelets("UUUURRRR"),
+            Err(FaceletError::WrongLength(8))
+        );
+        assert_eq!(
+            Cube::from_facelets("UUUURRRRFFFFDDDDLLLLBBBQ"),
+            Err(FaceletError::UnknownLetter {
+                position: 23,
+                letter: 'Q',
+            })
+        );
+        // Two stickers swapped on the same corner.
+        assert_eq!(
+            Cube::from_facelets("UUURURRRFFFFDDDDLLLLBBBB"),
+            Err(FaceletError::Invalid(InvalidCube::UnknownCorner {
+                slot: 3,
+                colors: [Color::B, Color::Y, Color::R],
+            }))
+        );
+    }
+}
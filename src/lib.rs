@@ -0,0 +1,20 @@
+pub mod algorithm;
+pub mod beginner;
+pub mod cache;
+pub mod cases;
+pub mod cll;
+pub mod coord;
+pub mod cube;
+pub mod eg;
+pub mod facelets;
+pub mod moves;
+pub mod net;
+pub mod ortega;
+pub mod parallel;
+pub mod pattern;
+pub mod pieces;
+pub mod scramble;
+pub mod solver;
+pub mod table;
+pub mod validate;
+pub mod watch;
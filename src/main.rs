@@ -1,16 +1,34 @@
+extern crate rustix;
 #[macro_use]
 extern crate text_io;
 
+use std::env;
+use std::fs;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-mod cube;
-mod solver;
+use rustix::beginner;
+use rustix::cache;
+use rustix::cube::Cube;
+use rustix::eg::EG_SETS;
+use rustix::moves::{parse_moves, Metric, Move, METRICS};
+use rustix::ortega;
+use rustix::parallel::available_threads;
+use rustix::pattern::Pattern;
+use rustix::pieces::STATES;
+use rustix::scramble::Scrambler;
+use rustix::solver::{MoveSet, Solver, Strategy};
+use rustix::table::{DistanceTable, PruningTables};
+use rustix::watch::Limits;
 
-use cube::Cube;
-use solver::Solver;
-
-fn print_cube(cube: &Cube) {
-    println!("{}", cube);
+fn print_cube(cube: &Cube, show_net: bool) {
+    if show_net {
+        println!("{}", cube.net());
+    } else {
+        println!("{}", cube);
+    }
 }
 
 fn print_solved_status(cube: &mut Cube) {
@@ -24,22 +42,268 @@ fn print_solved_status(cube: &mut Cube) {
     );
 }
 
-fn print_cube_and_solved_status(cube: &mut Cube) {
-    print_cube(cube);
+fn print_cube_and_solved_status(cube: &mut Cube, show_net: bool) {
+    print_cube(cube, show_net);
     print_solved_status(cube);
 }
 
-fn solve_cube(cube: Cube) -> Cube {
-    let mut solver = Solver::new();
-    solver.solve(cube)
+fn read_line(prompt: &str) -> String {
+    print!("{}", prompt);
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    // The newline after the command letter is still waiting to be read.
+    while line.trim().is_empty() {
+        line.clear();
+        if io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+    }
+    line
+}
+
+fn apply_notation(cube: &mut Cube) {
+    match parse_moves(&read_line("Enter moves (e.g. R U R' U'): ")) {
+        Ok(moves) => cube.apply_moves(&moves),
+        Err(err) => println!("{}", err),
+    }
+}
+
+fn load_position(cube: &mut Cube) {
+    match read_line("Enter a position number: ").trim().parse::<u32>() {
+        Ok(rank) if rank < STATES => *cube = Cube::unrank(rank),
+        _ => println!("Position numbers go from 0 to {}", STATES - 1),
+    }
+}
+
+fn import_facelets(cube: &mut Cube) {
+    match Cube::from_facelets(&read_line("Enter 24 facelets in URFDLB order: ")) {
+        Ok(imported) => *cube = imported,
+        Err(err) => println!("{}", err),
+    }
+}
+
+fn scramble_cube(cube: &mut Cube, scrambler: &mut Scrambler) {
+    let scramble = scrambler.scramble();
+    println!("Scramble: {}", scramble.algorithm);
+    *cube = scramble.cube;
+}
+
+fn scramble_eg_case(cube: &mut Cube, scrambler: &mut Scrambler) {
+    let line = read_line("Which set (CLL, EG-1 or EG-2)? ");
+    let chosen = line.trim().to_uppercase();
+    let set = match EG_SETS.iter().find(|set| set.to_string() == chosen) {
+        Some(&set) => set,
+        None => return println!("There are no cases called {}", line.trim()),
+    };
+    let scramble = scrambler.scramble_eg(set);
+    println!("Scramble: {}", scramble.algorithm);
+    *cube = scramble.cube;
+}
+
+fn solve_cube(cube: &mut Cube, solver: &mut Solver) {
+    match solver.solve(*cube) {
+        Ok(solution) => {
+            println!("Solution: {}", solution);
+            solution.algorithm.apply(cube);
+        }
+        Err(err) => println!("{}", err),
+    }
+}
+
+fn give_hint(cube: &Cube, solver: &mut Solver) {
+    match solver.hint(*cube) {
+        Ok(hint) => match hint.next {
+            Some(mv) => println!(
+                "Hint: try {} (solved in {} {})",
+                mv,
+                hint.distance,
+                solver.metric()
+            ),
+            None => println!("Hint: it's already solved"),
+        },
+        Err(err) => println!("{}", err),
+    }
+}
+
+fn solve_to_pattern(cube: &mut Cube, solver: &mut Solver) {
+    let line = read_line("Enter 24 facelets in URFDLB order, X for any colour: ");
+    let pattern = match Pattern::from_facelets(&line) {
+        Ok(pattern) => pattern,
+        Err(err) => return println!("{}", err),
+    };
+    match solver.solve_to(*cube, &pattern) {
+        Ok(solution) => {
+            println!("Solution: {}", solution);
+            solution.algorithm.apply(cube);
+        }
+        Err(err) => println!("{}", err),
+    }
+}
+
+fn solve_by_ortega(cube: &mut Cube) {
+    match ortega::solve(*cube) {
+        Ok(solution) => {
+            print!("{}", solution);
+            solution.algorithm().apply(cube);
+        }
+        Err(err) => println!("{}", err),
+    }
+}
+
+fn solve_like_a_beginner(cube: &mut Cube) {
+    match beginner::solve(*cube) {
+        Ok(solution) => {
+            print!("{}", solution);
+            println!("{} moves in all", solution.algorithm().htm());
+            solution.algorithm().apply(cube);
+        }
+        Err(err) => println!("{}", err),
+    }
+}
+
+fn switch_metric(solver: &mut Solver) {
+    let metric = match solver.metric() {
+        Metric::Htm => Metric::Qtm,
+        Metric::Qtm => Metric::Htm,
+    };
+    solver.set_metric(metric);
+    println!("Solutions are now shortest in {}", metric);
+}
+
+fn restrict_moves(solver: &mut Solver) {
+    let line = read_line("Enter the faces to turn (e.g. RU, or URF for all): ");
+    let faces: Result<Vec<_>, _> = line
+        .trim()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_string().parse::<Move>().map(|mv| mv.face()))
+        .collect();
+    match faces {
+        Ok(ref faces) if faces.iter().all(|face| face.is_some()) => {
+            let faces: Vec<_> = faces.iter().filter_map(|&face| face).collect();
+            let moves = MoveSet::new(&faces, solver.metric());
+            println!("Solving with {}", moves);
+            solver.set_moves(moves);
+        }
+        _ => println!("Faces are U, D, L, R, F and B"),
+    }
+}
+
+/// The argument after `option`, if it was given.
+fn option_value<'a>(args: &'a [String], option: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|arg| arg == option)
+        .and_then(|i| args.get(i + 1))
+}
+
+/// The directory given with `--cache`, or the usual one.
+fn cache_dir(args: &[String]) -> PathBuf {
+    option_value(args, "--cache").map_or_else(cache::default_dir, PathBuf::from)
+}
+
+/// The number given with `--threads`, or one per core.
+fn threads(args: &[String]) -> usize {
+    option_value(args, "--threads")
+        .and_then(|threads| threads.parse().ok())
+        .unwrap_or_else(available_threads)
+}
+
+fn build_tables(dir: &Path, threads: usize) -> io::Result<()> {
+    for &metric in &METRICS {
+        let moves = MoveSet::all(metric);
+        let path = cache::path(dir, cache::TableKind::Distance, &moves);
+        DistanceTable::build(&moves, threads).save(&path)?;
+        println!("Built {}", path.display());
+        let path = cache::path(dir, cache::TableKind::Pruning, &moves);
+        PruningTables::for_moves(&moves).save(&path)?;
+        println!("Built {}", path.display());
+    }
+    Ok(())
+}
+
+/// Checks every table in `dir`, returning whether they are all fine.
+fn verify_tables(dir: &Path) -> io::Result<bool> {
+    let files = cache::files(dir)?;
+    if files.is_empty() {
+        println!("No tables in {}", dir.display());
+    }
+    let mut all_fine = true;
+    for path in files {
+        match cache::read_any(&path) {
+            Ok((header, _)) => println!("{}: {}", path.display(), header),
+            Err(err) => {
+                println!("{}: {}", path.display(), err);
+                all_fine = false;
+            }
+        }
+    }
+    Ok(all_fine)
+}
+
+fn delete_tables(dir: &Path) -> io::Result<()> {
+    for path in cache::files(dir)? {
+        fs::remove_file(&path)?;
+        println!("Deleted {}", path.display());
+    }
+    Ok(())
+}
+
+/// Runs `rustix tables build|verify|delete [--cache DIR] [--threads N]`.
+fn manage_tables(args: &[String]) {
+    let dir = cache_dir(args);
+    let result = match args.get(2).map(String::as_str) {
+        Some("build") => build_tables(&dir, threads(args)).map(|_| true),
+        Some("verify") => verify_tables(&dir),
+        Some("delete") => delete_tables(&dir).map(|_| true),
+        _ => {
+            println!("Usage: rustix tables build|verify|delete [--cache DIR] [--threads N]");
+            Ok(false)
+        }
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            println!("{}: {}", dir.display(), err);
+            process::exit(1);
+        }
+    }
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).is_some_and(|arg| arg == "tables") {
+        manage_tables(&args);
+        return;
+    }
     let mut cube = Cube::new();
-    print_cube_and_solved_status(&mut cube);
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0);
+    let mut scrambler = Scrambler::new(seed);
+    // The distance table is loaded or built on the first solve and kept
+    // for the rest.
+    let mut solver = Solver::with_strategy(Strategy::DistanceTable);
+    solver.set_cache_dir(Some(cache_dir(&args)));
+    solver.set_threads(threads(&args));
+    // Searches that can't use a table, e.g. with opposite faces allowed,
+    // could otherwise keep the terminal waiting for ever.
+    solver.set_limits(Limits {
+        time: Some(Duration::from_secs(10)),
+        nodes: None,
+    });
+    solver.set_progress(|progress| {
+        println!(
+            "Searching {} moves deep: {} nodes looked at, {} waiting",
+            progress.depth, progress.nodes, progress.frontier
+        )
+    });
+    let mut show_net = args.iter().any(|arg| arg == "--net");
+    print_cube_and_solved_status(&mut cube, show_net);
 
     loop {
-        print!("Enter a letter (w/a/s/d/f/r/g/t/x/c/S/h/q): ");
+        print!("Enter a letter (w/a/s/d/f/r/g/t/x/c/m/n/l/p/i/v/R/E/S/H/P/O/B/Q/G/h/q): ");
         let _ = io::stdout().flush();
 
         let input: char = read!();
@@ -58,7 +322,21 @@ fn main() {
             'r' => cube.undo_front_twist(),
             'x' => cube.bottom_twist_back(),
             'c' => cube.bottom_twist(),
-            'S' => cube = solve_cube(cube),
+            'm' => apply_notation(&mut cube),
+            'n' => println!("Position number: {}", cube.rank()),
+            'l' => load_position(&mut cube),
+            'p' => println!("Facelets: {}", cube.to_facelets()),
+            'i' => import_facelets(&mut cube),
+            'v' => show_net = !show_net,
+            'R' => scramble_cube(&mut cube, &mut scrambler),
+            'E' => scramble_eg_case(&mut cube, &mut scrambler),
+            'S' => solve_cube(&mut cube, &mut solver),
+            'H' => give_hint(&cube, &mut solver),
+            'P' => solve_to_pattern(&mut cube, &mut solver),
+            'O' => solve_by_ortega(&mut cube),
+            'B' => solve_like_a_beginner(&mut cube),
+            'Q' => switch_metric(&mut solver),
+            'G' => restrict_moves(&mut solver),
             'e' => cube.tip_right(),
             'z' => cube.tip_left(),
             'h' => {
@@ -67,10 +345,24 @@ fn main() {
                 println!("t - twists RHS of cube away from screen");
                 println!("f - twists front face of cube clockwise");
                 println!("r - twists front face of cube anti-clockwise");
+                println!("m - applies moves in standard notation");
+                println!("n - prints the position number");
+                println!("l - loads a position by number");
+                println!("p - prints the facelets in URFDLB order");
+                println!("i - imports facelets in URFDLB order");
+                println!("v - switches between the 3D view and the unfolded net");
+                println!("R - scrambles the cube at random");
+                println!("E - scrambles into a CLL, EG-1 or EG-2 case to practise");
                 println!("S - Solves the cube!!!");
+                println!("H - suggests the next move without solving the cube");
+                println!("P - solves to a pattern with some stickers left out");
+                println!("O - solves step by step with the Ortega method");
+                println!("B - solves a corner at a time, the way beginners do");
+                println!("Q - switches solving between half and quarter turns");
+                println!("G - chooses which faces the solver may turn");
             }
             _ => {}
         }
-        print_cube_and_solved_status(&mut cube);
+        print_cube_and_solved_status(&mut cube, show_net);
     }
 }
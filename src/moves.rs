@@ -0,0 +1,446 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use algorithm::Algorithm;
+
+/// One of the six faces of the cube, named as in standard notation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Face {
+    U,
+    D,
+    L,
+    R,
+    F,
+    B,
+}
+
+/// An axis for whole-cube rotations. `X` follows R, `Y` follows U and
+/// `Z` follows F.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// How far a face or the whole cube is turned, looking at the face
+/// (or the face the axis is named after) head on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Amount {
+    Clockwise,
+    Half,
+    CounterClockwise,
+}
+
+/// A single move in standard (Singmaster) notation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Turn(Face, Amount),
+    Rotate(Axis, Amount),
+}
+
+/// How moves are counted when measuring how long a solution is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// Half turn metric: any turn of a face is one move.
+    Htm,
+    /// Quarter turn metric: a half turn is two moves.
+    Qtm,
+}
+
+pub const FACES: [Face; 6] = [Face::U, Face::D, Face::L, Face::R, Face::F, Face::B];
+
+pub const AXES: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+pub const AMOUNTS: [Amount; 3] = [Amount::Clockwise, Amount::Half, Amount::CounterClockwise];
+
+pub const METRICS: [Metric; 2] = [Metric::Htm, Metric::Qtm];
+
+impl Face {
+    /// The face on the other side of the cube.
+    pub fn opposite(self) -> Face {
+        match self {
+            Face::U => Face::D,
+            Face::D => Face::U,
+            Face::L => Face::R,
+            Face::R => Face::L,
+            Face::F => Face::B,
+            Face::B => Face::F,
+        }
+    }
+
+    /// The axis this face turns about.
+    pub fn axis(self) -> Axis {
+        match self {
+            Face::L | Face::R => Axis::X,
+            Face::U | Face::D => Axis::Y,
+            Face::F | Face::B => Axis::Z,
+        }
+    }
+
+    /// Where this face ends up when the whole cube is rotated a quarter
+    /// turn clockwise about `axis`.
+    pub fn rotated(self, axis: Axis) -> Face {
+        match (axis, self) {
+            (Axis::X, Face::F) => Face::U,
+            (Axis::X, Face::U) => Face::B,
+            (Axis::X, Face::B) => Face::D,
+            (Axis::X, Face::D) => Face::F,
+            (Axis::Y, Face::F) => Face::L,
+            (Axis::Y, Face::L) => Face::B,
+            (Axis::Y, Face::B) => Face::R,
+            (Axis::Y, Face::R) => Face::F,
+            (Axis::Z, Face::U) => Face::R,
+            (Axis::Z, Face::R) => Face::D,
+            (Axis::Z, Face::D) => Face::L,
+            (Axis::Z, Face::L) => Face::U,
+            (_, face) => face,
+        }
+    }
+
+    fn from_char(c: char) -> Option<Face> {
+        match c {
+            'U' => Some(Face::U),
+            'D' => Some(Face::D),
+            'L' => Some(Face::L),
+            'R' => Some(Face::R),
+            'F' => Some(Face::F),
+            'B' => Some(Face::B),
+            _ => None,
+        }
+    }
+}
+
+impl Axis {
+    /// The face that turns the same way as a rotation about this axis.
+    pub fn face(self) -> Face {
+        match self {
+            Axis::X => Face::R,
+            Axis::Y => Face::U,
+            Axis::Z => Face::F,
+        }
+    }
+
+    fn from_char(c: char) -> Option<Axis> {
+        match c {
+            'x' => Some(Axis::X),
+            'y' => Some(Axis::Y),
+            'z' => Some(Axis::Z),
+            _ => None,
+        }
+    }
+}
+
+impl Amount {
+    /// Number of clockwise quarter turns this amount is made up of.
+    pub fn quarter_turns(self) -> usize {
+        match self {
+            Amount::Clockwise => 1,
+            Amount::Half => 2,
+            Amount::CounterClockwise => 3,
+        }
+    }
+
+    /// The amount that `quarter_turns` clockwise quarter turns add up to,
+    /// or `None` if they add up to nothing at all.
+    pub fn from_quarter_turns(quarter_turns: usize) -> Option<Amount> {
+        match quarter_turns % 4 {
+            1 => Some(Amount::Clockwise),
+            2 => Some(Amount::Half),
+            3 => Some(Amount::CounterClockwise),
+            _ => None,
+        }
+    }
+
+    pub fn inverse(self) -> Amount {
+        match self {
+            Amount::Clockwise => Amount::CounterClockwise,
+            Amount::Half => Amount::Half,
+            Amount::CounterClockwise => Amount::Clockwise,
+        }
+    }
+}
+
+impl Move {
+    /// The move that undoes this one.
+    pub fn inverse(self) -> Move {
+        match self {
+            Move::Turn(face, amount) => Move::Turn(face, amount.inverse()),
+            Move::Rotate(axis, amount) => Move::Rotate(axis, amount.inverse()),
+        }
+    }
+
+    /// The face this move turns, or `None` for a whole-cube rotation.
+    pub fn face(self) -> Option<Face> {
+        match self {
+            Move::Turn(face, _) => Some(face),
+            Move::Rotate(..) => None,
+        }
+    }
+
+    /// The move as seen in a mirror placed across `axis`, e.g. `Axis::X`
+    /// swaps left and right so that `R` becomes `L'`.
+    pub fn mirror(self, axis: Axis) -> Move {
+        match self {
+            Move::Turn(face, amount) if face.axis() == axis => {
+                Move::Turn(face.opposite(), amount.inverse())
+            }
+            Move::Turn(face, amount) => Move::Turn(face, amount.inverse()),
+            Move::Rotate(rotation_axis, _) if rotation_axis == axis => self,
+            Move::Rotate(rotation_axis, amount) => Move::Rotate(rotation_axis, amount.inverse()),
+        }
+    }
+}
+
+impl Metric {
+    /// How many moves `mv` counts as. Rotations are always free.
+    pub fn cost(self, mv: Move) -> usize {
+        match (self, mv) {
+            (_, Move::Rotate(..)) => 0,
+            (Metric::Htm, Move::Turn(..)) => 1,
+            (Metric::Qtm, Move::Turn(_, Amount::Half)) => 2,
+            (Metric::Qtm, Move::Turn(..)) => 1,
+        }
+    }
+}
+
+impl From<Face> for char {
+    fn from(face: Face) -> char {
+        match face {
+            Face::U => 'U',
+            Face::D => 'D',
+            Face::L => 'L',
+            Face::R => 'R',
+            Face::F => 'F',
+            Face::B => 'B',
+        }
+    }
+}
+
+impl From<Axis> for char {
+    fn from(axis: Axis) -> char {
+        match axis {
+            Axis::X => 'x',
+            Axis::Y => 'y',
+            Axis::Z => 'z',
+        }
+    }
+}
+
+impl Display for Amount {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        match *self {
+            Amount::Clockwise => Ok(()),
+            Amount::Half => write!(formatter, "2"),
+            Amount::CounterClockwise => write!(formatter, "'"),
+        }
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        match *self {
+            Metric::Htm => write!(formatter, "HTM"),
+            Metric::Qtm => write!(formatter, "QTM"),
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        match *self {
+            Move::Turn(face, amount) => write!(formatter, "{}{}", char::from(face), amount),
+            Move::Rotate(axis, amount) => write!(formatter, "{}{}", char::from(axis), amount),
+        }
+    }
+}
+
+/// Error returned when a string is not valid move notation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// Byte offset of the offending token in the parsed string.
+    pub position: usize,
+    /// The text that could not be understood.
+    pub token: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The token is not a face turn or rotation.
+    UnknownMove,
+    /// A `(` was never closed.
+    UnclosedGroup,
+    /// A `)` had no matching `(`.
+    UnexpectedClose,
+    /// The repeat count after a group is not a number, or makes too many
+    /// moves.
+    BadRepeat,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        let what = match self.kind {
+            ParseErrorKind::UnknownMove => "unrecognised move",
+            ParseErrorKind::UnclosedGroup => "unclosed group",
+            ParseErrorKind::UnexpectedClose => "unmatched",
+            ParseErrorKind::BadRepeat => "bad repeat count",
+        };
+        write!(
+            formatter,
+            "{} `{}` at position {}",
+            what, self.token, self.position
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+impl FromStr for Move {
+    type Err = ParseError;
+
+    /// Parses a single move such as `R'` or `x2`.
+    fn from_str(s: &str) -> Result<Move, ParseError> {
+        let mut chars = s.chars();
+        let letter = chars.next();
+        let amount = match chars.as_str() {
+            "" => Some(Amount::Clockwise),
+            "2" | "2'" | "2’" => Some(Amount::Half),
+            "'" | "’" => Some(Amount::CounterClockwise),
+            _ => None,
+        };
+        if let (Some(c), Some(amount)) = (letter, amount) {
+            if let Some(face) = Face::from_char(c) {
+                return Ok(Move::Turn(face, amount));
+            }
+            if let Some(axis) = Axis::from_char(c) {
+                return Ok(Move::Rotate(axis, amount));
+            }
+        }
+        Err(ParseError {
+            kind: ParseErrorKind::UnknownMove,
+            position: 0,
+            token: s.to_string(),
+        })
+    }
+}
+
+/// Parses a sequence of moves such as `"R U R' U' F2 y"`.
+///
+/// See `Algorithm`'s `FromStr` for the accepted syntax.
+pub fn parse_moves(s: &str) -> Result<Vec<Move>, ParseError> {
+    s.parse::<Algorithm>().map(Algorithm::into_moves)
+}
+
+/// Formats a sequence of moves as space separated notation.
+pub fn format_moves(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|mv| mv.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{
+        format_moves, parse_moves, Amount, Axis, Face, Metric, Move, ParseError, ParseErrorKind,
+    };
+
+    #[test]
+    fn test_parse_single_moves() {
+        assert_eq!("R".parse(), Ok(Move::Turn(Face::R, Amount::Clockwise)));
+        assert_eq!(
+            "U'".parse(),
+            Ok(Move::Turn(Face::U, Amount::CounterClockwise))
+        );
+        assert_eq!("F2".parse(), Ok(Move::Turn(Face::F, Amount::Half)));
+        assert_eq!("B2'".parse(), Ok(Move::Turn(Face::B, Amount::Half)));
+        assert_eq!("y".parse(), Ok(Move::Rotate(Axis::Y, Amount::Clockwise)));
+        assert_eq!(
+            "x'".parse(),
+            Ok(Move::Rotate(Axis::X, Amount::CounterClockwise))
+        );
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let moves = parse_moves("R U R' U' F2 y").unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                Move::Turn(Face::R, Amount::Clockwise),
+                Move::Turn(Face::U, Amount::Clockwise),
+                Move::Turn(Face::R, Amount::CounterClockwise),
+                Move::Turn(Face::U, Amount::CounterClockwise),
+                Move::Turn(Face::F, Amount::Half),
+                Move::Rotate(Axis::Y, Amount::Clockwise),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_without_spaces() {
+        assert_eq!(parse_moves("RUR'U'"), parse_moves("R U R' U'"));
+    }
+
+    #[test]
+    fn test_parse_empty_string() {
+        assert_eq!(parse_moves("  "), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_error_reports_position_and_token() {
+        assert_eq!(
+            parse_moves("R U Q2 F"),
+            Err(ParseError {
+                kind: ParseErrorKind::UnknownMove,
+                position: 4,
+                token: "Q2".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_moves("R U3"),
+            Err(ParseError {
+                kind: ParseErrorKind::UnknownMove,
+                position: 2,
+                token: "U3".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_moves("R''"),
+            Err(ParseError {
+                kind: ParseErrorKind::UnknownMove,
+                position: 0,
+                token: "R''".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_single_move_from_str_rejects_sequences() {
+        assert!("R U".parse::<Move>().is_err());
+        assert!("".parse::<Move>().is_err());
+    }
+
+    #[test]
+    fn test_format_round_trips() {
+        let text = "R U2 R' D' L B2 x y' z2";
+        assert_eq!(format_moves(&parse_moves(text).unwrap()), text);
+    }
+
+    #[test]
+    fn test_metric_costs() {
+        let costs = |mv: &str| {
+            let mv: Move = mv.parse().unwrap();
+            (Metric::Htm.cost(mv), Metric::Qtm.cost(mv))
+        };
+        assert_eq!(costs("R"), (1, 1));
+        assert_eq!(costs("U'"), (1, 1));
+        assert_eq!(costs("F2"), (1, 2));
+        assert_eq!(costs("x2"), (0, 0));
+        assert_eq!(Metric::Qtm.to_string(), "QTM");
+    }
+}
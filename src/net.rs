@@ -0,0 +1,88 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use cube::Cube;
+use facelets::face_colors;
+use moves::Face;
+
+/// Draws every sticker of a cube, with the faces unfolded into a cross:
+///
+/// ```text
+///     U U
+///     U U
+/// L L F F R R B B
+/// L L F F R R B B
+///     D D
+///     D D
+/// ```
+pub struct Net<'a> {
+    cube: &'a Cube,
+}
+
+impl<'a> Net<'a> {
+    pub fn new(cube: &'a Cube) -> Self {
+        Net { cube }
+    }
+
+    /// Writes one row of stickers of each of `faces`, side by side.
+    fn write_row(&self, formatter: &mut Formatter, faces: &[Face], row: usize) -> FmtResult {
+        let stickers: Vec<String> = faces
+            .iter()
+            .flat_map(|&face| {
+                let colors = face_colors(self.cube, face);
+                vec![colors[row * 2], colors[row * 2 + 1]]
+            })
+            .map(|color| color.to_string())
+            .collect();
+        writeln!(formatter, "{}", stickers.join(" "))
+    }
+}
+
+impl<'a> Display for Net<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        for row in 0..2 {
+            write!(formatter, "    ")?;
+            self.write_row(formatter, &[Face::U], row)?;
+        }
+        for row in 0..2 {
+            self.write_row(formatter, &[Face::L, Face::F, Face::R, Face::B], row)?;
+        }
+        for row in 0..2 {
+            write!(formatter, "    ")?;
+            self.write_row(formatter, &[Face::D], row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use algorithm::Algorithm;
+    use cube::Cube;
+
+    #[test]
+    fn test_solved_net() {
+        let expected = "    y y
+    y y
+g g r r b b p p
+g g r r b b p p
+    w w
+    w w
+";
+        assert_eq!(Cube::new().net().to_string(), expected);
+    }
+
+    #[test]
+    fn test_net_shows_hidden_faces() {
+        let mut cube = Cube::new();
+        "R U".parse::<Algorithm>().unwrap().apply(&mut cube);
+        let expected = "    y y
+    r r
+r w b b y p g g
+g g r w b b y p
+    w p
+    w p
+";
+        assert_eq!(cube.net().to_string(), expected);
+    }
+}
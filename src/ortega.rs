@@ -0,0 +1,401 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use algorithm::Algorithm;
+use cases::{self, is_one_color, is_solved_as_held, layer_turns, recognize, turns, Case};
+use cube::{rotations, Cube};
+use moves::{Face, FACES};
+use solver::{SolveError, Solver};
+
+/// The phases of the Ortega method, in the order they are done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Make any one face a single colour, then hold it at the bottom.
+    FirstFace,
+    /// Orient the last layer: turn the top colour up on every top corner.
+    Oll,
+    /// Permute both layers: swap corners until every side is one colour.
+    Pbl,
+}
+
+pub const PHASES: [Phase; 3] = [Phase::FirstFace, Phase::Oll, Phase::Pbl];
+
+impl Phase {
+    /// Whether `cube`, held as it is, is where this phase leaves it.
+    pub fn is_done(self, cube: &Cube) -> bool {
+        match self {
+            Phase::FirstFace => is_one_color(cube, Face::D),
+            Phase::Oll => is_one_color(cube, Face::D) && is_one_color(cube, Face::U),
+            Phase::Pbl => is_solved_as_held(cube),
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        let name = match *self {
+            Phase::FirstFace => "first face",
+            Phase::Oll => "OLL",
+            Phase::Pbl => "PBL",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+/// The ways the top corners can be twisted once the bottom face is done,
+/// apart from all facing up already. The top colour is the one opposite
+/// the bottom face.
+pub const OLL_CASES: [Case; 7] = [
+    Case {
+        name: "Sune",
+        description: "one corner faces up and the other three show the top colour clockwise round the top",
+        notation: "R U R' U R U2 R'",
+    },
+    Case {
+        name: "Antisune",
+        description: "one corner faces up and the other three show the top colour anticlockwise round the top",
+        notation: "R U2 R' U' R U' R'",
+    },
+    Case {
+        name: "H",
+        description: "no corner faces up and the top colour shows as a pair on each of two opposite sides",
+        notation: "R2 U2 R U2 R2",
+    },
+    Case {
+        name: "Pi",
+        description: "no corner faces up, one side shows a pair of the top colour and the other two point away from each other",
+        notation: "F R U R' U' R U R' U' F'",
+    },
+    Case {
+        name: "U",
+        description: "two neighbouring corners face up and the other two show the top colour on the same side",
+        notation: "F R U R' U' F'",
+    },
+    Case {
+        name: "T",
+        description: "two neighbouring corners face up and the other two show the top colour on opposite sides",
+        notation: "R U R' U' R' F R F'",
+    },
+    Case {
+        name: "L",
+        description: "two diagonally opposite corners face up",
+        notation: "F R' F' R U R U' R'",
+    },
+];
+
+/// The ways the corners can be mixed up within their layers once the top
+/// and bottom faces are done, apart from both layers being solved already.
+///
+/// A layer with matching colours on just one side ("headlights") needs two
+/// neighbouring corners swapped. A layer with no matching side needs two
+/// diagonally opposite corners swapped.
+pub const PBL_CASES: [Case; 8] = [
+    Case {
+        name: "adjacent swap on top",
+        description: "the top has headlights on one side and the bottom is solved",
+        notation: "R U R' U' R' F R2 U' R' U' R U R' F'",
+    },
+    Case {
+        name: "diagonal swap on top",
+        description: "the top has no headlights and the bottom is solved",
+        notation: "F R U' R' U' R U R' F' R U R' U' R' F R F'",
+    },
+    Case {
+        name: "adjacent swap on the bottom",
+        description: "the top is solved and the bottom has headlights on one side, so turn the cube over and swap them on top",
+        notation: "z2 R U R' U' R' F R2 U' R' U' R U R' F' z2",
+    },
+    Case {
+        name: "diagonal swap on the bottom",
+        description: "the top is solved and the bottom has no headlights, so turn the cube over and swap them on top",
+        notation: "z2 F R U' R' U' R U R' F' R U R' U' R' F R F' z2",
+    },
+    Case {
+        name: "double adjacent swap",
+        description: "both layers have headlights on one side",
+        notation: "R2 U' B2 U2 R2 U' R2",
+    },
+    Case {
+        name: "double diagonal swap",
+        description: "neither layer has headlights",
+        notation: "R2 F2 R2",
+    },
+    Case {
+        name: "adjacent top, diagonal bottom",
+        description: "the top has headlights on one side and the bottom has none",
+        notation: "R U' R F2 R' U R'",
+    },
+    Case {
+        name: "diagonal top, adjacent bottom",
+        description: "the top has no headlights and the bottom has them on one side, so turn the cube over first",
+        notation: "z2 R U' R F2 R' U R' z2",
+    },
+];
+
+/// One phase of a solve: the case it started from, what was done about it
+/// and why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub phase: Phase,
+    /// The name of the case, e.g. "Sune", or "skip" if there was nothing
+    /// to do but turn a layer.
+    pub case: String,
+    /// Every move of the phase, including turns of U or D to line the case
+    /// up and to finish off.
+    pub algorithm: Algorithm,
+    /// What the case looked like and what the moves did about it, in one
+    /// line.
+    pub explanation: String,
+}
+
+impl Display for Step {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        write!(formatter, "{} ({}): ", self.phase, self.case)?;
+        if self.algorithm.is_empty() {
+            write!(formatter, "nothing to do")?;
+        } else {
+            write!(formatter, "{}", self.algorithm)?;
+        }
+        write!(formatter, " - {}", self.explanation)
+    }
+}
+
+/// A solve by the Ortega method, one step per phase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrtegaSolution {
+    pub steps: Vec<Step>,
+}
+
+impl OrtegaSolution {
+    /// Every step's moves one after the other.
+    pub fn algorithm(&self) -> Algorithm {
+        self.steps
+            .iter()
+            .fold(Algorithm::default(), |algorithm, step| {
+                algorithm.then(&step.algorithm)
+            })
+    }
+}
+
+impl Display for OrtegaSolution {
+    /// Writes one line per step.
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        for step in &self.steps {
+            writeln!(formatter, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Solves `cube` the way a person using the Ortega method would: make the
+/// quickest face to make, orient the top corners, then put both layers'
+/// corners in place. Solutions are much longer than the solver's, but
+/// every step is one a person can learn.
+pub fn solve(cube: Cube) -> Result<OrtegaSolution, SolveError> {
+    let mut held = cube;
+    let first_face = first_face(&mut held)?;
+    // Turning U doesn't change which top corners face up, so OLL has
+    // nothing to finish with.
+    let oll = last_layer_step(
+        &mut held,
+        Phase::Oll,
+        &OLL_CASES,
+        &turns(Face::U),
+        &[Algorithm::default()],
+    );
+    let pbl = last_layer_step(
+        &mut held,
+        Phase::Pbl,
+        &PBL_CASES,
+        &layer_turns(),
+        &turns(Face::U),
+    );
+    Ok(OrtegaSolution {
+        steps: vec![first_face, oll, pbl],
+    })
+}
+
+/// Finds the quickest face to make, makes it and turns the cube so it is
+/// at the bottom.
+fn first_face(cube: &mut Cube) -> Result<Step, SolveError> {
+    let solution = Solver::new().solve_until(*cube, |cube| {
+        FACES.iter().any(|&face| is_one_color(cube, face))
+    })?;
+    solution.algorithm.apply(cube);
+    let rotation = rotations()
+        .into_iter()
+        .find(|rotation| {
+            let mut turned = *cube;
+            rotation.apply(&mut turned);
+            Phase::FirstFace.is_done(&turned)
+        })
+        .expect("the search stops once a face is done");
+    rotation.apply(cube);
+
+    let color = cube.block(4).color(Face::D);
+    let mut explanation = if solution.algorithm.is_empty() {
+        format!("the {} face is already done", color)
+    } else {
+        format!(
+            "makes the {} face, the quickest one, in {} moves",
+            color,
+            solution.htm()
+        )
+    };
+    if !rotation.is_empty() {
+        explanation.push_str(", then turns the cube to hold it at the bottom");
+    }
+    Ok(Step {
+        phase: Phase::FirstFace,
+        case: format!("{} face", color),
+        algorithm: solution.algorithm.then(&rotation),
+        explanation,
+    })
+}
+
+/// Does whatever `phase` needs to `cube`, held after one of `setups`,
+/// along with whichever of `finishes` completes the phase.
+fn last_layer_step(
+    cube: &mut Cube,
+    phase: Phase,
+    cases: &[Case],
+    setups: &[Algorithm],
+    finishes: &[Algorithm],
+) -> Step {
+    let done = |cube: &Cube| phase.is_done(cube);
+    // Nothing but a finishing turn may be needed.
+    if let Some(finish) = cases::finish(cube, finishes, done) {
+        finish.apply(cube);
+        let explanation = if finish.is_empty() {
+            "already done"
+        } else {
+            "only the top needs turning"
+        };
+        return Step {
+            phase,
+            case: "skip".to_string(),
+            algorithm: finish,
+            explanation: explanation.to_string(),
+        };
+    }
+    let recognition = recognize(cube, cases, setups, finishes, done)
+        .unwrap_or_else(|| panic!("no {} case fits the cube", phase));
+    let algorithm = recognition.algorithm();
+    algorithm.apply(cube);
+    let mut explanation = recognition.case.description.to_string();
+    if !recognition.setup.is_empty() {
+        explanation.push_str(&format!("; {} lines it up", recognition.setup));
+    }
+    if !recognition.finish.is_empty() {
+        explanation.push_str(&format!("; {} finishes", recognition.finish));
+    }
+    Step {
+        phase,
+        case: recognition.case.name.to_string(),
+        algorithm,
+        explanation,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{solve, turns, Phase, OLL_CASES, PBL_CASES, PHASES};
+    use algorithm::{cube_after, Algorithm};
+    use cube::Cube;
+    use moves::Face;
+    use pieces::Pieces;
+    use scramble::Scrambler;
+
+    /// Applies each step in turn, checking it gets as far as it should.
+    fn check_steps(cube: Cube) {
+        let solution = solve(cube).unwrap();
+        let mut cube = cube;
+        for (step, &phase) in solution.steps.iter().zip(PHASES.iter()) {
+            assert_eq!(step.phase, phase);
+            step.algorithm.apply(&mut cube);
+            assert!(phase.is_done(&cube), "{}", step);
+        }
+        assert_eq!(solution.steps.len(), 3);
+    }
+
+    #[test]
+    fn test_solved_cube_skips_everything() {
+        let solution = solve(Cube::new()).unwrap();
+        assert!(solution.algorithm().is_empty());
+        assert_eq!(solution.steps[0].case, "w face");
+        assert_eq!(solution.steps[1].case, "skip");
+        assert_eq!(solution.steps[2].case, "skip");
+    }
+
+    #[test]
+    fn test_cases_keep_the_bottom_face() {
+        for case in OLL_CASES.iter().chain(PBL_CASES.iter()) {
+            let mut cube = Cube::new();
+            case.algorithm().inverse().apply(&mut cube);
+            assert!(Phase::FirstFace.is_done(&cube), "{}", case.name);
+            assert!(!Phase::Pbl.is_done(&cube), "{}", case.name);
+        }
+        for case in PBL_CASES.iter() {
+            let mut cube = Cube::new();
+            case.algorithm().inverse().apply(&mut cube);
+            assert!(Phase::Oll.is_done(&cube), "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn test_every_oll_case_is_recognised() {
+        // Each twist of the top corners, with their colours on the bottom
+        // face where they belong.
+        for rank in 0..27 {
+            let mut pieces = Pieces::solved();
+            let (a, b, c) = (rank % 3, rank / 3 % 3, rank / 9);
+            pieces.orientation = [a, b, c, (6 - a - b - c) % 3, 0, 0, 0, 0];
+            let solution = solve(pieces.to_cube()).unwrap();
+            assert_eq!(solution.steps[0].algorithm, Algorithm::default());
+            assert_eq!(solution.steps[1].case == "skip", rank == 0);
+        }
+    }
+
+    #[test]
+    fn test_every_pbl_case_is_recognised() {
+        let mut names = Vec::new();
+        for top in &turns(Face::U) {
+            for case in PBL_CASES.iter() {
+                let mut cube = Cube::new();
+                case.algorithm().inverse().apply(&mut cube);
+                top.apply(&mut cube);
+                let solution = solve(cube).unwrap();
+                let mut solved = cube;
+                solution.algorithm().apply(&mut solved);
+                assert!(solved.is_solved(), "{}", case.name);
+                names.push(solution.steps[2].case.clone());
+            }
+        }
+        for case in PBL_CASES.iter() {
+            assert!(names.iter().any(|name| name == case.name), "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn test_steps_reach_each_phase() {
+        check_steps(cube_after("R U2 F' R U R' F2"));
+        check_steps(cube_after("x R2 U"));
+        let mut scrambler = Scrambler::new(21);
+        for _ in 0..5 {
+            check_steps(scrambler.scramble().cube);
+        }
+    }
+
+    #[test]
+    fn test_explanations_name_the_case() {
+        let solution = solve(cube_after("R U R' U R U2 R'")).unwrap();
+        let oll = &solution.steps[1];
+        assert_eq!(oll.case, "Antisune");
+        assert!(oll.explanation.starts_with("one corner faces up"));
+        assert!(solution.to_string().contains("OLL (Antisune): "));
+        assert_eq!(
+            solve(cube_after("U")).unwrap().steps[2].explanation,
+            "only the top needs turning"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// Below this many items per thread, starting threads isn't worth it.
+const MIN_PER_THREAD: usize = 256;
+
+/// How many threads to use when not told otherwise: one per core.
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Splits `items` into up to `threads` runs and works out `f` of each run
+/// on a thread of its own, returning the results in order. `f` is given
+/// where its run starts in `items` along with the run.
+///
+/// For anything built from the results in order to come out the same
+/// however many threads there are, `f` of a run should amount to `f` of
+/// each part of it in turn, as it does when `f` just looks at each item.
+pub fn map_chunks<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &[T]) -> R + Sync,
+{
+    let threads = threads.min(items.len() / MIN_PER_THREAD);
+    if threads <= 1 {
+        return vec![f(0, items)];
+    }
+    let size = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(size)
+            .enumerate()
+            .map(|(i, chunk)| scope.spawn(move || f(i * size, chunk)))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("worker thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{available_threads, map_chunks};
+
+    #[test]
+    fn test_map_chunks_keeps_the_order() {
+        let items: Vec<u32> = (0..10_000).collect();
+        let square = |start: usize, chunk: &[u32]| {
+            chunk
+                .iter()
+                .enumerate()
+                .map(|(i, &item)| (start + i, item * item))
+                .collect::<Vec<_>>()
+        };
+        let expected = square(0, &items);
+        for threads in 1..6 {
+            let results = map_chunks(&items, threads, square);
+            assert!(results.len() <= threads);
+            let flattened: Vec<_> = results.into_iter().flatten().collect();
+            assert_eq!(flattened, expected);
+        }
+        assert_eq!(
+            map_chunks(&[] as &[u32], 4, |_, chunk| chunk.len()),
+            vec![0]
+        );
+        assert!(available_threads() >= 1);
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use cube::{rotations, Color, Cube, BLOCKS};
+use facelets::{face_colors, face_index, FaceletError, FACE_ORDER};
+use moves::Face;
+
+/// A cube with some stickers left blank, written `Color::X`. It matches
+/// any cube showing the given colours on the other stickers, however the
+/// cube is held, so "a white face" is the pattern with the D face white
+/// and everything else blank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    /// Four stickers for each face of `FACE_ORDER`, read as in a facelet
+    /// string.
+    stickers: [Color; 24],
+}
+
+impl Default for Pattern {
+    fn default() -> Self {
+        Pattern::blank()
+    }
+}
+
+impl Pattern {
+    /// The pattern every cube matches.
+    pub fn blank() -> Self {
+        Pattern {
+            stickers: [Color::X; 24],
+        }
+    }
+
+    /// The stickers of a solved cube on `faces`, with the rest blank.
+    pub fn solved_faces(faces: &[Face]) -> Self {
+        faces.iter().fold(Pattern::blank(), |pattern, &face| {
+            pattern.with_face(face, BLOCKS[0].color(face))
+        })
+    }
+
+    /// This pattern with every sticker of `face` coloured `color`.
+    pub fn with_face(mut self, face: Face, color: Color) -> Self {
+        let start = face_index(face) * 4;
+        for sticker in &mut self.stickers[start..start + 4] {
+            *sticker = color;
+        }
+        self
+    }
+
+    /// Reads 24 stickers, four per face in `FACE_ORDER`, using the face
+    /// letters `URFDLB` for colours and `X` for blank stickers. Whitespace
+    /// is ignored.
+    pub fn from_facelets(facelets: &str) -> Result<Self, FaceletError> {
+        let mut stickers = Vec::with_capacity(24);
+        for (position, letter) in facelets.char_indices() {
+            if letter.is_whitespace() {
+                continue;
+            }
+            let color = match letter {
+                'X' | 'x' => Color::X,
+                _ => FACE_ORDER
+                    .iter()
+                    .find(|&&face| char::from(face) == letter)
+                    .map(|&face| BLOCKS[0].color(face))
+                    .ok_or(FaceletError::UnknownLetter { position, letter })?,
+            };
+            stickers.push(color);
+        }
+        if stickers.len() != 24 {
+            return Err(FaceletError::WrongLength(stickers.len()));
+        }
+        let mut pattern = Pattern::blank();
+        pattern.stickers.copy_from_slice(&stickers);
+        Ok(pattern)
+    }
+
+    /// Whether `cube` shows this pattern when held some way or other.
+    pub fn matches(&self, cube: &Cube) -> bool {
+        rotations().iter().any(|rotation| {
+            let mut held = *cube;
+            rotation.apply(&mut held);
+            self.matches_as_held(&held)
+        })
+    }
+
+    /// Whether `cube` shows this pattern held exactly as it is.
+    pub fn matches_as_held(&self, cube: &Cube) -> bool {
+        FACE_ORDER.iter().enumerate().all(|(f, &face)| {
+            let colors = face_colors(cube, face);
+            self.stickers[f * 4..f * 4 + 4]
+                .iter()
+                .zip(colors.iter())
+                .all(|(&wanted, &color)| wanted == Color::X || wanted == color)
+        })
+    }
+}
+
+impl Display for Pattern {
+    /// Writes the pattern as `Pattern::from_facelets` reads it.
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        for &color in self.stickers.iter() {
+            let letter = FACE_ORDER
+                .iter()
+                .find(|&&face| BLOCKS[0].color(face) == color)
+                .map_or('X', |&face| char::from(face));
+            write!(formatter, "{}", letter)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::Pattern;
+    use algorithm::cube_after;
+    use cube::Color;
+    use facelets::FaceletError;
+    use moves::Face;
+
+    #[test]
+    fn test_blank_pattern_matches_anything() {
+        assert!(Pattern::blank().matches(&cube_after("R U F' L2")));
+    }
+
+    #[test]
+    fn test_face_pattern() {
+        let white_face = Pattern::blank().with_face(Face::D, Color::W);
+        assert_eq!(white_face.to_string(), "XXXXXXXXXXXXDDDDXXXXXXXX");
+
+        assert!(white_face.matches(&cube_after("U D' y")));
+        assert!(white_face.matches_as_held(&cube_after("U")));
+        // Held upside down the white face is on top, which still counts.
+        assert!(white_face.matches(&cube_after("x2 U")));
+        assert!(!white_face.matches_as_held(&cube_after("x2 U")));
+        assert!(!white_face.matches(&cube_after("R")));
+    }
+
+    #[test]
+    fn test_pattern_from_facelets() {
+        let top_oriented = Pattern::from_facelets("UUUU XXXX XXXX XXXX XXXX XXXX").unwrap();
+        assert_eq!(top_oriented, Pattern::solved_faces(&[Face::U]));
+        assert!(top_oriented.matches(&cube_after("U x2")));
+        assert!(!top_oriented.matches(&cube_after("R")));
+
+        assert_eq!(
+            Pattern::from_facelets("UUUU"),
+            Err(FaceletError::WrongLength(4))
+        );
+        assert_eq!(
+            Pattern::from_facelets("UUUUXXXXXXXXXXXXXXXXXXX?"),
+            Err(FaceletError::UnknownLetter {
+                position: 23,
+                letter: '?',
+            })
+        );
+    }
+}
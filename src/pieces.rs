@@ -0,0 +1,353 @@
+use cube::{rotations, Block, Color, Cube, BLOCKS};
+use moves::Face::*;
+use moves::{Face, Move};
+
+/// The outward faces of each slot, clockwise as seen from outside the
+/// cube and starting with the U or D face. Slots are numbered as in
+/// `Cube::block`, and corner `n` is the piece whose home is slot `n`.
+pub const SLOT_FACES: [[Face; 3]; 8] = [
+    [U, L, B],
+    [U, B, R],
+    [U, F, L],
+    [U, R, F],
+    [D, B, L],
+    [D, R, B],
+    [D, L, F],
+    [D, F, R],
+];
+
+/// Number of states a cube can be in, ignoring how it is held: the seven
+/// corners other than DBL can be arranged 7! ways, and six of them twisted
+/// 3^6 ways with the seventh's twist following from the rest.
+pub const STATES: u32 = 5040 * 729;
+
+/// Slots other than DBL, in the order they are ranked.
+const FREE_SLOTS: [usize; 7] = [0, 1, 2, 3, 5, 6, 7];
+
+/// The cube described piece by piece rather than block face by block
+/// face.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pieces {
+    /// The corner sitting in each slot.
+    pub permutation: [u8; 8],
+    /// How far each slot's corner is twisted: 0 if its U or D colour is
+    /// on the U or D face, 1 if it is one face clockwise from there and
+    /// 2 if it is one face counter clockwise.
+    pub orientation: [u8; 8],
+}
+
+/// The colours of `corner` in the order of `SLOT_FACES`, on a solved cube.
+pub fn corner_colors(corner: usize) -> [Color; 3] {
+    let faces = SLOT_FACES[corner];
+    [
+        BLOCKS[corner].color(faces[0]),
+        BLOCKS[corner].color(faces[1]),
+        BLOCKS[corner].color(faces[2]),
+    ]
+}
+
+/// The colours showing on the outside of `slot`, in the order of
+/// `SLOT_FACES`.
+pub fn slot_colors(cube: &Cube, slot: usize) -> [Color; 3] {
+    let block = cube.block(slot);
+    let faces = SLOT_FACES[slot];
+    [
+        block.color(faces[0]),
+        block.color(faces[1]),
+        block.color(faces[2]),
+    ]
+}
+
+/// Works out which corner, twisted how far, shows `colors`.
+pub fn identify_corner(colors: [Color; 3]) -> Option<(u8, u8)> {
+    for corner in 0..8 {
+        let home = corner_colors(corner);
+        for twist in 0..3 {
+            if (0..3).all(|i| colors[(twist + i) % 3] == home[i]) {
+                return Some((corner as u8, twist as u8));
+            }
+        }
+    }
+    None
+}
+
+/// All 24 ways a block can be held.
+fn orientations() -> Vec<Block> {
+    let mut upright = BLOCKS[0];
+    let tipped = [
+        upright,
+        upright.tip_back(),
+        upright.tip_back().tip_back(),
+        upright.tip_forwards(),
+        upright.tip_right(),
+        upright.tip_left(),
+    ];
+    let mut all = Vec::with_capacity(24);
+    for &block in &tipped {
+        let mut block = block;
+        for _ in 0..4 {
+            all.push(block);
+            block = block.turn_right();
+        }
+    }
+    all
+}
+
+impl Pieces {
+    pub fn solved() -> Self {
+        Pieces {
+            permutation: [0, 1, 2, 3, 4, 5, 6, 7],
+            orientation: [0; 8],
+        }
+    }
+
+    /// What doing `mv` does to the corners, for use with `Pieces::apply`.
+    pub fn effect_of(mv: Move) -> Pieces {
+        let mut cube = Cube::new();
+        cube.apply_move(mv);
+        Pieces::from_cube(&cube).expect("moves only move pieces around")
+    }
+
+    /// Moves the corners as `effect` says, where `effect` is what the move
+    /// does to a solved cube. This is much quicker than doing the move on
+    /// a `Cube`.
+    pub fn apply(&mut self, effect: &Pieces) {
+        let before = *self;
+        for slot in 0..8 {
+            let from = effect.permutation[slot] as usize;
+            self.permutation[slot] = before.permutation[from];
+            self.orientation[slot] = (before.orientation[from] + effect.orientation[slot]) % 3;
+        }
+    }
+
+    /// Reads the corners off `cube`, or returns `None` if some slot shows a
+    /// combination of colours that no corner has.
+    pub fn from_cube(cube: &Cube) -> Option<Pieces> {
+        let mut pieces = Pieces::solved();
+        for slot in 0..8 {
+            let (corner, twist) = identify_corner(slot_colors(cube, slot))?;
+            pieces.permutation[slot] = corner;
+            pieces.orientation[slot] = twist;
+        }
+        Some(pieces)
+    }
+
+    /// Builds the `Cube` with these corners. For any cube made of properly
+    /// coloured blocks, `Pieces::from_cube(&cube).unwrap().to_cube() ==
+    /// cube`.
+    pub fn to_cube(&self) -> Cube {
+        let orientations = orientations();
+        let mut blocks = BLOCKS;
+        for (slot, block) in blocks.iter_mut().enumerate() {
+            let home = corner_colors(self.permutation[slot] as usize);
+            let twist = self.orientation[slot] as usize;
+            let faces = SLOT_FACES[slot];
+            *block = *orientations
+                .iter()
+                .find(|candidate| {
+                    (0..3).all(|i| candidate.color(faces[(twist + i) % 3]) == home[i])
+                })
+                .expect("every corner fits every slot some way up");
+        }
+        Cube::from_blocks(blocks)
+    }
+
+    /// Numbers the state from 0 to `STATES - 1`. The DBL corner must be
+    /// in its own slot and untwisted, as it is on a canonical cube.
+    pub fn rank(&self) -> u32 {
+        debug_assert!(self.permutation[4] == 4 && self.orientation[4] == 0);
+        self.permutation_rank() * 729 + self.orientation_rank()
+    }
+
+    /// The state numbered `rank` by `Pieces::rank`.
+    pub fn unrank(rank: u32) -> Pieces {
+        assert!(rank < STATES, "there are only {} states", STATES);
+        let mut pieces = Pieces::solved();
+        pieces.set_permutation_rank(rank / 729);
+        pieces.set_orientation_rank(rank % 729);
+        pieces
+    }
+
+    /// Ranks the arrangement of the free corners from 0 to 5039 by
+    /// their Lehmer code.
+    pub fn permutation_rank(&self) -> u32 {
+        let mut rank = 0;
+        for (i, &slot) in FREE_SLOTS.iter().enumerate() {
+            let corner = self.permutation[slot];
+            let smaller_after = FREE_SLOTS[i + 1..]
+                .iter()
+                .filter(|&&later| self.permutation[later] < corner)
+                .count();
+            rank = rank * (7 - i as u32) + smaller_after as u32;
+        }
+        rank
+    }
+
+    /// Ranks the twists of the first six free corners from 0 to 728 as a
+    /// base 3 number.
+    pub fn orientation_rank(&self) -> u32 {
+        FREE_SLOTS[..6].iter().fold(0, |rank, &slot| {
+            rank * 3 + u32::from(self.orientation[slot])
+        })
+    }
+
+    /// Arranges the free corners as numbered by `permutation_rank`.
+    pub fn set_permutation_rank(&mut self, mut rank: u32) {
+        let mut digits = [0; 7];
+        for i in (0..7).rev() {
+            digits[i] = (rank % (7 - i as u32)) as usize;
+            rank /= 7 - i as u32;
+        }
+        let mut unused = [0, 1, 2, 3, 5, 6, 7];
+        for (i, &slot) in FREE_SLOTS.iter().enumerate() {
+            self.permutation[slot] = unused[digits[i]];
+            unused.copy_within(digits[i] + 1..7 - i, digits[i]);
+        }
+    }
+
+    /// Twists the free corners as numbered by `orientation_rank`, with
+    /// the last one twisted to make the total a whole turn.
+    pub fn set_orientation_rank(&mut self, mut rank: u32) {
+        let mut total = 0;
+        for &slot in FREE_SLOTS[..6].iter().rev() {
+            self.orientation[slot] = (rank % 3) as u8;
+            total += rank % 3;
+            rank /= 3;
+        }
+        self.orientation[FREE_SLOTS[6]] = ((3 - total % 3) % 3) as u8;
+    }
+
+    /// The same state seen by someone who can never move the DBL slot,
+    /// such as anyone turning only U, R and F: colours are relabelled so
+    /// that whatever corner sits there, however it is twisted, counts as
+    /// being at home. Doing the same moves to this and to the real cube
+    /// solves both at once, the real one held some other way up.
+    pub fn anchored(&self) -> Pieces {
+        // The solved cube held with this cube's DBL slot corner in place.
+        let reference = rotations()
+            .iter()
+            .map(|rotation| {
+                let mut cube = Cube::new();
+                rotation.apply(&mut cube);
+                Pieces::from_cube(&cube).expect("rotations only move pieces around")
+            })
+            .find(|held| {
+                held.permutation[4] == self.permutation[4]
+                    && held.orientation[4] == self.orientation[4]
+            })
+            .expect("any corner can be held in any slot any way up");
+        let mut home = [0; 8];
+        for (slot, &corner) in reference.permutation.iter().enumerate() {
+            home[corner as usize] = slot as u8;
+        }
+        let mut anchored = Pieces::solved();
+        for slot in 0..8 {
+            let corner = home[self.permutation[slot] as usize];
+            anchored.permutation[slot] = corner;
+            anchored.orientation[slot] =
+                (self.orientation[slot] + 3 - reference.orientation[corner as usize]) % 3;
+        }
+        anchored
+    }
+
+    /// Total twist of all the corners, which is always 0 mod 3 on a cube
+    /// that can be solved.
+    pub fn total_twist(&self) -> u8 {
+        self.orientation.iter().sum::<u8>() % 3
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{Pieces, STATES};
+    use algorithm::{cube_after, Algorithm};
+    use cube::{rotations, Cube};
+
+    #[test]
+    fn test_solved_cube_has_solved_pieces() {
+        assert_eq!(Pieces::from_cube(&Cube::new()), Some(Pieces::solved()));
+    }
+
+    #[test]
+    fn test_u_turn_cycles_top_corners_without_twisting() {
+        let pieces = Pieces::from_cube(&cube_after("U")).unwrap();
+        assert_eq!(pieces.permutation, [2, 0, 3, 1, 4, 5, 6, 7]);
+        assert_eq!(pieces.orientation, [0; 8]);
+    }
+
+    #[test]
+    fn test_r_turn_twists_corners() {
+        let pieces = Pieces::from_cube(&cube_after("R")).unwrap();
+        assert_eq!(pieces.permutation, [0, 3, 2, 7, 4, 1, 6, 5]);
+        assert_eq!(pieces.orientation, [0, 1, 0, 2, 0, 2, 0, 1]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for moves in &["", "R", "U F2 R'", "R U R' U' F2 D L' B", "x y2 R z'"] {
+            let cube = cube_after(moves);
+            let pieces = Pieces::from_cube(&cube).unwrap();
+            assert_eq!(pieces.to_cube(), cube, "{}", moves);
+            assert_eq!(Pieces::from_cube(&pieces.to_cube()), Some(pieces));
+        }
+    }
+
+    #[test]
+    fn test_total_twist_of_scrambled_cube_is_zero() {
+        let pieces = Pieces::from_cube(&cube_after("R U F' L2 D B' R")).unwrap();
+        assert_eq!(pieces.total_twist(), 0);
+    }
+
+    #[test]
+    fn test_solved_ranks_zero() {
+        assert_eq!(Pieces::solved().rank(), 0);
+        assert_eq!(Pieces::unrank(0), Pieces::solved());
+    }
+
+    #[test]
+    fn test_rank_round_trips_for_every_state() {
+        for rank in 0..STATES {
+            let pieces = Pieces::unrank(rank);
+            assert_eq!(pieces.total_twist(), 0);
+            assert_eq!(pieces.rank(), rank);
+        }
+    }
+
+    #[test]
+    fn test_last_rank_is_last_permutation() {
+        let pieces = Pieces::unrank(STATES - 1);
+        assert_eq!(pieces.permutation, [7, 6, 5, 3, 4, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_applying_effects_matches_doing_moves() {
+        let algorithm: Algorithm = "R U' F2 D L' B2 x y' z2".parse().unwrap();
+        let mut pieces = Pieces::solved();
+        for &mv in algorithm.moves() {
+            pieces.apply(&Pieces::effect_of(mv));
+        }
+        assert_eq!(pieces.to_cube(), cube_after("R U' F2 D L' B2 x y' z2"));
+    }
+
+    #[test]
+    fn test_anchored_ignores_how_the_cube_is_held() {
+        let moves = "R U' F2 R2 U";
+        let expected = Pieces::from_cube(&cube_after(moves)).unwrap();
+        for rotation in rotations() {
+            let held = format!("{} {}", rotation, moves);
+            let pieces = Pieces::from_cube(&cube_after(&held)).unwrap();
+            assert_eq!(pieces.anchored(), expected, "{}", held);
+        }
+        assert_eq!(Pieces::solved().anchored(), Pieces::solved());
+    }
+
+    #[test]
+    fn test_twisted_corner_is_detected() {
+        let mut pieces = Pieces::solved();
+        pieces.orientation[5] = 1;
+        let cube = pieces.to_cube();
+        assert_eq!(Pieces::from_cube(&cube), Some(pieces));
+        assert_eq!(pieces.total_twist(), 1);
+    }
+}
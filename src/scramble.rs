@@ -0,0 +1,263 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use algorithm::Algorithm;
+use cube::Cube;
+use eg::{self, EgSet};
+use moves::Move;
+use pieces::{Pieces, STATES};
+use solver::GENERATORS;
+
+/// Scrambles that can be solved in fewer moves than this are thrown away,
+/// as competition rules require.
+pub const MIN_MOVES: usize = 4;
+
+/// How far from solved the scrambler's table of nearby states reaches.
+const TABLE_DEPTH: usize = 5;
+
+/// Deepest forward search needed on top of the table, since no state is
+/// more than 11 moves from solved.
+const MAX_SEARCH_DEPTH: usize = 6;
+
+/// A random cube and a way of getting there from solved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scramble {
+    pub cube: Cube,
+    /// Turns `Cube::new()` into `cube`, using U, R and F only. It is as
+    /// short as possible.
+    pub algorithm: Algorithm,
+}
+
+/// Picks states uniformly at random from every state the cube can be in,
+/// so every state is as likely as every other. The same seed always gives
+/// the same scrambles.
+pub struct Scrambler {
+    rng: SplitMix64,
+    effects: Vec<Pieces>,
+    /// Every state within `TABLE_DEPTH` moves of solved, with the move
+    /// that takes it one step closer.
+    nearby: HashMap<u32, (usize, Option<Move>)>,
+}
+
+impl Scrambler {
+    pub fn new(seed: u64) -> Self {
+        let effects: Vec<Pieces> = GENERATORS.iter().map(|&mv| Pieces::effect_of(mv)).collect();
+        let mut nearby = HashMap::new();
+        nearby.insert(0, (0, None));
+        let mut frontier = vec![Pieces::solved()];
+        for depth in 1..TABLE_DEPTH + 1 {
+            let mut next = Vec::new();
+            for pieces in &frontier {
+                for (i, effect) in effects.iter().enumerate() {
+                    let mut neighbour = *pieces;
+                    neighbour.apply(effect);
+                    if let Entry::Vacant(entry) = nearby.entry(neighbour.rank()) {
+                        entry.insert((depth, Some(GENERATORS[i].inverse())));
+                        next.push(neighbour);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        Scrambler {
+            rng: SplitMix64(seed),
+            effects,
+            nearby,
+        }
+    }
+
+    /// A random state needing at least `MIN_MOVES` moves to solve.
+    pub fn scramble(&mut self) -> Scramble {
+        loop {
+            let rank = self.rng.below(u64::from(STATES)) as u32;
+            let solution = self.shortest_solution(Pieces::unrank(rank));
+            if solution.len() >= MIN_MOVES {
+                return Scramble {
+                    cube: Cube::unrank(rank),
+                    algorithm: Algorithm::new(solution).inverse(),
+                };
+            }
+        }
+    }
+
+    /// A random state in `set`, for practising its cases: the first face
+    /// made on the bottom, the bottom corners arranged as `set` has them
+    /// and the top corners in one of its cases. Every such state is as
+    /// likely as every other.
+    pub fn scramble_eg(&mut self, set: EgSet) -> Scramble {
+        loop {
+            let mut pieces = Pieces::solved();
+            for slot in (1..4).rev() {
+                let other = self.rng.below(slot as u64 + 1) as usize;
+                pieces.permutation.swap(slot, other);
+            }
+            for slot in 0..3 {
+                pieces.orientation[slot] = self.rng.below(3) as u8;
+            }
+            pieces.orientation[3] = (3 - pieces.total_twist()) % 3;
+            // DBL has to stay where it is for ranking, so swap other pairs.
+            match set {
+                EgSet::Cll => {}
+                EgSet::Eg1 => pieces.permutation.swap(6, 7),
+                EgSet::Eg2 => pieces.permutation.swap(5, 6),
+            }
+            let cube = pieces.to_cube();
+            if eg::recognize(&cube).map(|(found, _)| found) != Some(set) {
+                continue;
+            }
+            let solution = self.shortest_solution(pieces);
+            if solution.len() >= MIN_MOVES {
+                return Scramble {
+                    cube,
+                    algorithm: Algorithm::new(solution).inverse(),
+                };
+            }
+        }
+    }
+
+    /// Finds an optimal solution by searching forwards from `pieces` until
+    /// a state in the table turns up. Searching `n` moves deep finds any
+    /// solution up to `n + TABLE_DEPTH` moves long, so the first depth
+    /// with a hit gives the shortest solution.
+    fn shortest_solution(&self, pieces: Pieces) -> Vec<Move> {
+        for depth in 0..MAX_SEARCH_DEPTH + 1 {
+            let mut path = Vec::with_capacity(depth);
+            let mut best = None;
+            self.search(pieces, depth, &mut path, &mut best);
+            if let Some((_, mut moves)) = best {
+                let mut pieces = pieces;
+                for &mv in &moves {
+                    pieces.apply(&Pieces::effect_of(mv));
+                }
+                while let Some(&(_, Some(mv))) = self.nearby.get(&pieces.rank()) {
+                    pieces.apply(&Pieces::effect_of(mv));
+                    moves.push(mv);
+                }
+                return moves;
+            }
+        }
+        unreachable!("no state is more than 11 moves from solved");
+    }
+
+    /// Tries every `depth` move sequence from `pieces`, keeping the one
+    /// that lands closest to solved in `best`.
+    fn search(
+        &self,
+        pieces: Pieces,
+        depth: usize,
+        path: &mut Vec<Move>,
+        best: &mut Option<(usize, Vec<Move>)>,
+    ) {
+        if depth == 0 {
+            if let Some(&(distance, _)) = self.nearby.get(&pieces.rank()) {
+                if best.as_ref().is_none_or(|&(d, _)| distance < d) {
+                    *best = Some((distance, path.clone()));
+                }
+            }
+            return;
+        }
+        let last_face = path.last().and_then(|mv| mv.face());
+        for (i, effect) in self.effects.iter().enumerate() {
+            let mv = GENERATORS[i];
+            if mv.face() == last_face {
+                continue;
+            }
+            let mut next = pieces;
+            next.apply(effect);
+            path.push(mv);
+            self.search(next, depth - 1, path, best);
+            path.pop();
+        }
+    }
+}
+
+/// A small, fast random number generator whose output never changes for
+/// a given seed, whatever platform or version of Rust it runs on.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to but not including `bound`, with every number
+    /// equally likely.
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let n = self.next();
+            if n < zone {
+                return n % bound;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{Scrambler, MIN_MOVES};
+    use algorithm::Algorithm;
+    use cube::Cube;
+    use eg::{recognize, EG_SETS};
+    use pieces::Pieces;
+    use solver::Solver;
+
+    #[test]
+    fn test_scramble_reaches_its_cube() {
+        let mut scrambler = Scrambler::new(1);
+        for _ in 0..5 {
+            let scramble = scrambler.scramble();
+            let mut cube = Cube::new();
+            scramble.algorithm.apply(&mut cube);
+            assert_eq!(cube, scramble.cube, "{}", scramble.algorithm);
+            assert!(scramble.algorithm.len() >= MIN_MOVES);
+            assert!(scramble.algorithm.len() <= 11);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_scrambles() {
+        let mut a = Scrambler::new(42);
+        let mut b = Scrambler::new(42);
+        for _ in 0..3 {
+            assert_eq!(a.scramble(), b.scramble());
+        }
+        assert_ne!(Scrambler::new(43).scramble(), Scrambler::new(42).scramble());
+    }
+
+    #[test]
+    fn test_eg_scrambles_stay_in_their_set() {
+        let mut scrambler = Scrambler::new(3);
+        for &set in &EG_SETS {
+            for _ in 0..3 {
+                let scramble = scrambler.scramble_eg(set);
+                let mut cube = Cube::new();
+                scramble.algorithm.apply(&mut cube);
+                assert_eq!(cube, scramble.cube, "{}", scramble.algorithm);
+                assert!(scramble.algorithm.len() >= MIN_MOVES);
+                assert_eq!(recognize(&cube).map(|(found, _)| found), Some(set));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shortest_solution_matches_breadth_first_solver() {
+        let scrambler = Scrambler::new(7);
+        for moves in &["", "R", "R U F'", "R U F' R2 U'"] {
+            let mut cube = Cube::new();
+            moves.parse::<Algorithm>().unwrap().apply(&mut cube);
+
+            let found = scrambler.shortest_solution(Pieces::from_cube(&cube).unwrap());
+            let mut solved = cube;
+            solved.apply_moves(&found);
+
+            assert!(solved.is_solved(), "{}", moves);
+            assert_eq!(found.len(), Solver::new().solve(cube).unwrap().htm());
+        }
+    }
+}
@@ -1,89 +1,1049 @@
-use std::collections::{HashSet, VecDeque};
-use cube::Cube;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use algorithm::Algorithm;
+use cube::{rotations, Cube};
+use moves::{Amount, Face, Metric, Move, AMOUNTS, FACES};
+use parallel::{self, available_threads};
+use pattern::Pattern;
+use pieces::Pieces;
+use table::{DistanceTable, PruningTables};
+use validate::InvalidCube;
+use watch::{CancelToken, Limits, Progress, ProgressCallback, Watch};
+
+/// How many states a breadth first search hands out to its threads at a
+/// time.
+const BATCH: usize = 1 << 16;
+
+/// Turns of the U, R and F faces. These leave the DBL corner where it is,
+/// so every state reachable from them is in a fixed orientation and
+/// nothing is lost by never turning the other three faces.
+pub const GENERATORS: [Move; 9] = [
+    Move::Turn(Face::U, Amount::Clockwise),
+    Move::Turn(Face::U, Amount::Half),
+    Move::Turn(Face::U, Amount::CounterClockwise),
+    Move::Turn(Face::R, Amount::Clockwise),
+    Move::Turn(Face::R, Amount::Half),
+    Move::Turn(Face::R, Amount::CounterClockwise),
+    Move::Turn(Face::F, Amount::Clockwise),
+    Move::Turn(Face::F, Amount::Half),
+    Move::Turn(Face::F, Amount::CounterClockwise),
+];
+
+/// Whether `mv` straight after `last` can be left out of a search, as
+/// some other sequence no longer does the same thing.
+///
+/// In the half turn metric turning the same face twice in a row is never
+/// shorter than turning it once. In the quarter turn metric the only pair
+/// of turns of one face worth making is `U U`, as `U' U'` does the same.
+/// Turns of opposite faces commute, so only one order of them is needed.
+pub fn is_redundant(metric: Metric, last: Option<Move>, mv: Move) -> bool {
+    let (last_face, face) = match (last.and_then(Move::face), mv.face()) {
+        (Some(last_face), Some(face)) => (last_face, face),
+        _ => return false,
+    };
+    if last_face == face {
+        match metric {
+            Metric::Htm => true,
+            Metric::Qtm => !(last == Some(mv) && mv == Move::Turn(face, Amount::Clockwise)),
+        }
+    } else {
+        let order = |face| FACES.iter().position(|&f| f == face);
+        last_face == face.opposite() && order(face) < order(last_face)
+    }
+}
+
+/// The faces a solver may turn and how its turns are counted. Turning
+/// only some of the faces keeps the cube within a subgroup of all its
+/// states, e.g. `<R,U>` for two generator practice.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveSet {
+    faces: Vec<Face>,
+    metric: Metric,
+}
+
+impl MoveSet {
+    /// Turns of any of `faces`, which are kept in the order of `FACES`.
+    pub fn new(faces: &[Face], metric: Metric) -> Self {
+        MoveSet {
+            faces: FACES
+                .iter()
+                .cloned()
+                .filter(|face| faces.contains(face))
+                .collect(),
+            metric,
+        }
+    }
+
+    /// Turns of U, R and F, which are enough to solve any cube.
+    pub fn all(metric: Metric) -> Self {
+        MoveSet::new(&[Face::U, Face::R, Face::F], metric)
+    }
+
+    pub fn faces(&self) -> &[Face] {
+        &self.faces
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    /// Every turn of the set's faces that counts as one move in its
+    /// metric.
+    pub fn moves(&self) -> Vec<Move> {
+        let amounts: &[Amount] = match self.metric {
+            Metric::Htm => &AMOUNTS,
+            Metric::Qtm => &[Amount::Clockwise, Amount::CounterClockwise],
+        };
+        self.faces
+            .iter()
+            .flat_map(|&face| amounts.iter().map(move |&amount| Move::Turn(face, amount)))
+            .collect()
+    }
+
+    /// A rotation after which all the set's faces are among U, R and F,
+    /// along with the set as seen after it. Searching that way up means
+    /// the DBL slot never moves. There is no such rotation if the set
+    /// turns two opposite faces, as then every slot is on a turning face.
+    pub fn search_frame(&self) -> Option<(Algorithm, MoveSet)> {
+        rotations().into_iter().find_map(|rotation| {
+            let faces: Vec<Face> = [Face::U, Face::R, Face::F]
+                .iter()
+                .cloned()
+                .filter(|&face| {
+                    let turn = Algorithm::new(vec![Move::Turn(face, Amount::Clockwise)]);
+                    let held = turn.conjugate(&rotation).moves()[0].face();
+                    held.is_some_and(|held| self.faces.contains(&held))
+                })
+                .collect();
+            if faces.len() == self.faces.len() {
+                Some((rotation, MoveSet::new(&faces, self.metric)))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl Display for MoveSet {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        let faces: Vec<String> = self
+            .faces
+            .iter()
+            .map(|&face| char::from(face).to_string())
+            .collect();
+        write!(formatter, "<{}>", faces.join(","))
+    }
+}
+
+/// `cube` as a search turning only U, R and F after `rotation` sees it:
+/// held that way up, with whatever corner is in the DBL slot counted as
+/// being at home. See `Pieces::anchored`.
+pub fn search_start(cube: &Cube, rotation: &Algorithm) -> Result<Pieces, InvalidCube> {
+    let mut held = *cube;
+    rotation.apply(&mut held);
+    Ok(held.validate()?.anchored())
+}
+
+/// How a `Solver` looks for solutions. Every strategy finds a solution
+/// that is as short as possible.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Searches outwards from the cube one move at a time. Needs no setup
+    /// but is slow on cubes far from solved.
+    BreadthFirst,
+    /// Walks down a `DistanceTable`. Building the table takes a moment
+    /// the first time, after which every solve is practically instant.
+    DistanceTable,
+    /// Iterative deepening A*: depth first searches with a growing move
+    /// limit, cutting off branches that `PruningTables` show can't be
+    /// solved within it. Uses hardly any memory.
+    IdaStar,
+    /// Searches outwards from the cube and back from the solved cube at
+    /// the same time until the two meet in the middle. Looks at far fewer
+    /// states than `BreadthFirst` and needs no setup.
+    Bidirectional,
+}
 
 pub struct Solver {
+    strategy: Strategy,
+    moves: MoveSet,
     checked: HashSet<Cube>,
+    cache: Option<PathBuf>,
+    threads: usize,
+    table: Option<DistanceTable>,
+    pruning: Option<PruningTables>,
+    limits: Limits,
+    cancel: CancelToken,
+    progress: Option<ProgressCallback>,
+}
+
+/// The outcome of a solve: how to solve the cube and what it cost to
+/// find out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution {
+    /// The moves that take the scrambled cube to a solved one.
+    pub algorithm: Algorithm,
+    /// Number of states looked at.
+    pub nodes: usize,
+    pub elapsed: Duration,
+}
+
+impl Solution {
+    pub fn moves(&self) -> &[Move] {
+        self.algorithm.moves()
+    }
+
+    pub fn htm(&self) -> usize {
+        self.algorithm.htm()
+    }
+
+    pub fn length(&self, metric: Metric) -> usize {
+        self.algorithm.length(metric)
+    }
+
+    pub fn qtm(&self) -> usize {
+        self.algorithm.qtm()
+    }
+}
+
+impl Display for Solution {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        if self.algorithm.is_empty() {
+            write!(formatter, "already solved")?;
+        } else {
+            write!(formatter, "{}", self.algorithm)?;
+        }
+        write!(
+            formatter,
+            " ({} HTM, {} QTM) - {} nodes in {:?}",
+            self.htm(),
+            self.qtm(),
+            self.nodes,
+            self.elapsed
+        )
+    }
+}
+
+/// A nudge towards solved, for someone solving the cube by hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hint {
+    /// A move that takes the cube one move closer to solved, or `None` if
+    /// it is solved already.
+    pub next: Option<Move>,
+    /// The fewest moves that solve the cube, counted in the solver's
+    /// metric.
+    pub distance: usize,
+}
+
+/// Why no solution was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    /// The cube can't be solved by any sequence of moves.
+    Invalid(InvalidCube),
+    /// The cube could be solved, but not by turning only the faces the
+    /// solver is allowed to turn.
+    Unreachable,
+    /// The request needs the allowed moves to leave a corner alone, and
+    /// they turn two opposite faces.
+    Unsupported,
+    /// The search ran out of time or states before finding a solution.
+    Timeout(Progress),
+    /// The search was stopped by its `CancelToken`.
+    Cancelled(Progress),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        match *self {
+            SolveError::Invalid(ref err) => write!(formatter, "cube can't be solved: {}", err),
+            SolveError::Unreachable => {
+                write!(formatter, "cube can't be solved with the allowed moves")
+            }
+            SolveError::Unsupported => {
+                write!(formatter, "can't do that while turning opposite faces")
+            }
+            SolveError::Timeout(progress) => write!(
+                formatter,
+                "gave up after {} nodes, the solution is at least {} moves long",
+                progress.nodes, progress.depth
+            ),
+            SolveError::Cancelled(progress) => write!(
+                formatter,
+                "cancelled after {} nodes, the solution is at least {} moves long",
+                progress.nodes, progress.depth
+            ),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+impl From<InvalidCube> for SolveError {
+    fn from(err: InvalidCube) -> Self {
+        SolveError::Invalid(err)
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver::new()
+    }
 }
 
 impl Solver {
+    /// A solver that searches breadth first for solutions that are
+    /// shortest in the half turn metric.
     pub fn new() -> Self {
+        Solver::with_strategy(Strategy::BreadthFirst)
+    }
+
+    pub fn with_strategy(strategy: Strategy) -> Self {
         Solver {
+            strategy,
+            moves: MoveSet::all(Metric::Htm),
             checked: HashSet::new(),
+            cache: None,
+            threads: available_threads(),
+            table: None,
+            pruning: None,
+            limits: Limits::default(),
+            cancel: CancelToken::new(),
+            progress: None,
+        }
+    }
+
+    /// A solver that walks down an already built `table`, with whatever
+    /// moves and metric the table was built for.
+    pub fn with_table(table: DistanceTable) -> Self {
+        Solver {
+            moves: table.moves().clone(),
+            table: Some(table),
+            ..Solver::with_strategy(Strategy::DistanceTable)
+        }
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.moves.metric()
+    }
+
+    /// Makes solutions as short as possible in `metric` from now on.
+    pub fn set_metric(&mut self, metric: Metric) {
+        let moves = MoveSet::new(self.moves.faces(), metric);
+        self.set_moves(moves);
+    }
+
+    pub fn moves(&self) -> &MoveSet {
+        &self.moves
+    }
+
+    /// Only turns the faces in `moves` from now on, counting them in its
+    /// metric. Tables built for other moves are thrown away.
+    ///
+    /// Sets that turn two opposite faces can't be searched with the DBL
+    /// slot held still, so they are always searched breadth first, from
+    /// both ends with `Strategy::Bidirectional` and otherwise from the
+    /// cube. That is only practical for small sets such as `<U,D>`.
+    pub fn set_moves(&mut self, moves: MoveSet) {
+        if moves != self.moves {
+            self.moves = moves;
+            self.table = None;
+            self.pruning = None;
+        }
+    }
+
+    /// The directory tables are loaded from and saved to, if any.
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache.as_deref()
+    }
+
+    /// Loads tables from files in `dir` from now on rather than working
+    /// them out, saving them there the first time. With `None` tables are
+    /// always worked out.
+    pub fn set_cache_dir(&mut self, dir: Option<PathBuf>) {
+        self.cache = dir;
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Searches and builds tables on up to `threads` threads from now on,
+    /// one per core to begin with. Solutions don't depend on it.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Gives up on searches that go past `limits` from now on, failing
+    /// with `SolveError::Timeout`. Building a table is never cut short.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// A token that stops this solver's searches with
+    /// `SolveError::Cancelled` when cancelled, from any thread, until it
+    /// is reset.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Calls `callback` with the progress of every search from now on, at
+    /// each new depth and every so often in between.
+    pub fn set_progress<F>(&mut self, callback: F)
+    where
+        F: FnMut(Progress) + Send + 'static,
+    {
+        self.progress = Some(Box::new(callback));
+    }
+
+    /// Finds the shortest sequence of the allowed moves that solves
+    /// `cube`, after checking that there is one.
+    pub fn solve(&mut self, cube: Cube) -> Result<Solution, SolveError> {
+        self.watched(|solver, watch| solver.solve_watched(cube, watch))
+    }
+
+    fn solve_watched(&mut self, cube: Cube, watch: &mut Watch) -> Result<Solution, SolveError> {
+        cube.validate()?;
+        if self.moves.search_frame().is_none() && self.strategy != Strategy::Bidirectional {
+            return self.breadth_first(cube, watch);
         }
+        match self.strategy {
+            Strategy::BreadthFirst => self.breadth_first(cube, watch),
+            Strategy::Bidirectional => self.bidirectional(cube, watch),
+            Strategy::DistanceTable => {
+                let (moves, cache, threads) = (&self.moves, self.cache.as_deref(), self.threads);
+                let table = self.table.get_or_insert_with(|| match cache {
+                    Some(dir) => DistanceTable::cached(dir, moves, threads),
+                    None => DistanceTable::build(moves, threads),
+                });
+                let (algorithm, nodes) = table.solve(&cube)?;
+                Ok(Solution {
+                    algorithm,
+                    nodes,
+                    elapsed: watch.elapsed(),
+                })
+            }
+            Strategy::IdaStar => self.ida_star(cube, watch),
+        }
+    }
+
+    /// Runs `search` under a `Watch` with the solver's limits, token and
+    /// progress callback.
+    fn watched<T, S>(&mut self, search: S) -> Result<T, SolveError>
+    where
+        S: FnOnce(&mut Self, &mut Watch) -> Result<T, SolveError>,
+    {
+        let mut watch = Watch::new(self.limits, self.cancel.clone(), self.progress.take());
+        let result = watch.check().and_then(|_| search(self, &mut watch));
+        self.progress = watch.into_callback();
+        result
     }
 
-    /// Returns true if passed a solved cube, else twists the cube
-    /// till solved.
-    pub fn solve(&mut self, cube: Cube) -> Cube {
-        // this isn't really a stack but sshhh!
-        let mut stack = VecDeque::new();
-        let mut operations = VecDeque::new();
-        stack.push_back(cube);
-        while let Some(cube) = stack.pop_front() {
-            let op = operations.pop_front();
-            println!("{:?}", op);
-            if cube.is_solved() {
-                println!("Solved with operations: {:?}", op);
-                return cube;
+    /// The first move of a shortest solution to `cube`, and how long that
+    /// solution is, without giving the rest of it away.
+    pub fn hint(&mut self, cube: Cube) -> Result<Hint, SolveError> {
+        let solution = self.solve(cube)?;
+        Ok(Hint {
+            next: solution.moves().first().cloned(),
+            distance: solution.length(self.metric()),
+        })
+    }
+
+    /// Every solution to `cube` as short as possible, then every one a move
+    /// longer and so on, up to `extra` moves longer than the shortest.
+    /// Solutions are worked out as they are asked for.
+    ///
+    /// Only move sets that never turn two opposite faces are supported,
+    /// as the search holds the cube so it only turns U, R and F; others
+    /// fail with `SolveError::Unsupported`. No two of those turns commute,
+    /// so no two solutions differ only in the order of their turns.
+    /// Sequences that contain turns cancelling out are left out, as are
+    /// any that pass through the solved state on the way.
+    ///
+    /// Only finding how long the shortest solutions are is held to the
+    /// solver's limits.
+    pub fn solutions(&mut self, cube: Cube, extra: usize) -> Result<Solutions<'_>, SolveError> {
+        cube.validate()?;
+        if self.moves.search_frame().is_none() {
+            return Err(SolveError::Unsupported);
+        }
+        let (rank, shortest) = self.watched(|solver, watch| {
+            let pruning = solver.pruning_tables();
+            let rank = search_start(&cube, pruning.rotation())?.rank();
+            if !pruning.is_reachable(rank) {
+                return Err(SolveError::Unreachable);
             }
-            self.checked.insert(cube);
+            let mut shortest = pruning.estimate(rank);
+            while !deepen(pruning, rank, shortest, &mut Vec::new(), watch)? {
+                shortest += 1;
+                if shortest >= pruning.distance_bound() {
+                    return Err(SolveError::Unreachable);
+                }
+            }
+            Ok((rank, shortest))
+        })?;
+        let pruning = self.pruning_tables();
+        Ok(Solutions {
+            pruning,
+            start: rank,
+            length: shortest,
+            longest: shortest + extra,
+            stack: Vec::new(),
+            path: Vec::new(),
+        })
+    }
 
-            let mut twisty_cube = cube;
-            twisty_cube.twist();
-            if !self.checked.contains(&twisty_cube) {
-                stack.push_back(twisty_cube);
-                stack.push_back(twisty_cube);
-                let op_string = "bottom_twist,";
-                if (op.is_some()) {
-                    op_string = "{:?} {}", op, op_string;
-                    operations.push_back(op_string);
-                } else {
-                    operations.push_back(op_string);
+    /// Finds the shortest sequence of the allowed moves that leaves
+    /// `cube` showing `pattern`, held any way up. Blank stickers in the
+    /// pattern can be anything, so this can aim for e.g. a single solved
+    /// face. Always searches breadth first, whatever the strategy, as the
+    /// tables only know the way to a fully solved cube.
+    pub fn solve_to(&mut self, cube: Cube, pattern: &Pattern) -> Result<Solution, SolveError> {
+        self.solve_until(cube, |cube| pattern.matches(cube))
+    }
+
+    /// Finds the shortest sequence of the allowed moves after which `goal`
+    /// holds, searching breadth first like `solve_to`. The search may hold
+    /// the cube some other way up, so `goal` shouldn't depend on which way
+    /// up the cube is.
+    pub fn solve_until<G>(&mut self, cube: Cube, goal: G) -> Result<Solution, SolveError>
+    where
+        G: Fn(&Cube) -> bool + Sync,
+    {
+        self.watched(|solver, watch| solver.solve_until_watched(cube, goal, watch))
+    }
+
+    fn solve_until_watched<G>(
+        &mut self,
+        cube: Cube,
+        goal: G,
+        watch: &mut Watch,
+    ) -> Result<Solution, SolveError>
+    where
+        G: Fn(&Cube) -> bool + Sync,
+    {
+        cube.validate()?;
+        // The goal is about colours, so the cube can't be relabelled
+        // the way `breadth_first` does it. Holding it so the DBL slot
+        // stays put still keeps rotated copies out of the search.
+        let (rotation, generators) = match self.moves.search_frame() {
+            Some((rotation, moves)) => (rotation, moves.moves()),
+            None => (Algorithm::default(), self.moves.moves()),
+        };
+        let mut held = cube;
+        rotation.apply(&mut held);
+        let algorithm = self.search_for(held, &generators, goal, watch)?;
+        Ok(Solution {
+            algorithm: algorithm.simplified().conjugate(&rotation),
+            nodes: watch.nodes(),
+            elapsed: watch.elapsed(),
+        })
+    }
+
+    fn pruning_tables(&mut self) -> &PruningTables {
+        let (moves, cache) = (&self.moves, self.cache.as_deref());
+        self.pruning.get_or_insert_with(|| match cache {
+            Some(dir) => PruningTables::cached(dir, moves),
+            None => PruningTables::for_moves(moves),
+        })
+    }
+
+    fn ida_star(&mut self, cube: Cube, watch: &mut Watch) -> Result<Solution, SolveError> {
+        let pruning = self.pruning_tables();
+        let rank = search_start(&cube, pruning.rotation())?.rank();
+        if !pruning.is_reachable(rank) {
+            return Err(SolveError::Unreachable);
+        }
+        let mut path = Vec::new();
+        let mut limit = pruning.estimate(rank);
+        while !deepen(pruning, rank, limit, &mut path, watch)? {
+            limit += 1;
+            if limit >= pruning.distance_bound() {
+                return Err(SolveError::Unreachable);
+            }
+        }
+        let moves = pruning.tables().moves();
+        let algorithm: Algorithm = path.iter().map(|&mv| moves[mv]).collect();
+        Ok(Solution {
+            algorithm: algorithm.simplified().conjugate(pruning.rotation()),
+            nodes: watch.nodes(),
+            elapsed: watch.elapsed(),
+        })
+    }
+
+    fn breadth_first(&mut self, cube: Cube, watch: &mut Watch) -> Result<Solution, SolveError> {
+        // Where possible, hold the cube so the allowed moves never turn
+        // the DBL slot and relabel it so the corner there is at home.
+        // Every state found from here on has that corner at home too, so
+        // rotated copies of a state can't sneak into `checked` and the
+        // search can stop at exactly `Cube::new()`.
+        let frame = self.moves.search_frame();
+        let anchored = frame.is_some();
+        let (cube, rotation, generators) = match frame {
+            Some((rotation, moves)) => (
+                search_start(&cube, &rotation)?.to_cube(),
+                rotation,
+                moves.moves(),
+            ),
+            None => (cube, Algorithm::default(), self.moves.moves()),
+        };
+        let solved = Cube::new();
+        let goal = |cube: &Cube| {
+            if anchored {
+                *cube == solved
+            } else {
+                cube.is_solved()
+            }
+        };
+        let algorithm = self.search_for(cube, &generators, goal, watch)?;
+        Ok(Solution {
+            algorithm: algorithm.simplified().conjugate(&rotation),
+            nodes: watch.nodes(),
+            elapsed: watch.elapsed(),
+        })
+    }
+
+    fn bidirectional(&mut self, cube: Cube, watch: &mut Watch) -> Result<Solution, SolveError> {
+        // Held as in `breadth_first` only `Cube::new()` counts as solved.
+        // Otherwise the search back starts from every way of holding a
+        // solved cube.
+        let (cube, rotation, generators, goals) = match self.moves.search_frame() {
+            Some((rotation, moves)) => (
+                search_start(&cube, &rotation)?.to_cube(),
+                rotation,
+                moves.moves(),
+                vec![Cube::new()],
+            ),
+            None => (
+                cube,
+                Algorithm::default(),
+                self.moves.moves(),
+                rotations()
+                    .iter()
+                    .map(|rotation| {
+                        let mut solved = Cube::new();
+                        rotation.apply(&mut solved);
+                        solved
+                    })
+                    .collect(),
+            ),
+        };
+        let metric = self.moves.metric();
+        let mut forwards = Frontier::new(vec![cube]);
+        let mut backwards = Frontier::new(goals);
+
+        let mut meeting = backwards.index.get(&cube).map(|&theirs| (0, theirs));
+        let (forward, backward) = loop {
+            if let Some(meeting) = meeting {
+                break meeting;
+            }
+            let (ahead, behind) = (forwards.layer_len(), backwards.layer_len());
+            if ahead == 0 || behind == 0 {
+                return Err(SolveError::Unreachable);
+            }
+            // Grow whichever side has less to look at.
+            meeting = if ahead <= behind {
+                forwards.expand(metric, &generators, &backwards, self.threads, watch)?
+            } else {
+                backwards
+                    .expand(metric, &generators, &forwards, self.threads, watch)?
+                    .map(|(theirs, ours)| (ours, theirs))
+            };
+            // Had the two sides met any sooner they would have been found
+            // to.
+            let depth = forwards.depth + backwards.depth + 1;
+            watch.deepen(depth, forwards.layer_len() + backwards.layer_len());
+        };
+
+        // The moves found going back lead from the solved cube to the
+        // meeting point, so undoing them leads the rest of the way.
+        let algorithm =
+            path_to(&forwards.found, forward).then(&path_to(&backwards.found, backward).inverse());
+        Ok(Solution {
+            algorithm: algorithm.simplified().conjugate(&rotation),
+            nodes: watch.nodes(),
+            elapsed: watch.elapsed(),
+        })
+    }
+
+    /// Searches breadth first from `cube` with `generators` for the
+    /// nearest state `goal` accepts, returning the moves that reach it.
+    fn search_for<G>(
+        &mut self,
+        cube: Cube,
+        generators: &[Move],
+        goal: G,
+        watch: &mut Watch,
+    ) -> Result<Algorithm, SolveError>
+    where
+        G: Fn(&Cube) -> bool + Sync,
+    {
+        let metric = self.moves.metric();
+        self.checked.clear();
+        self.checked.insert(cube);
+
+        // Every state found so far, with the state it was reached from
+        // and the move that got there, nearest first. States are looked
+        // at in that order, so this doubles as the queue.
+        let mut found: Vec<(Cube, Option<(usize, Move)>)> = vec![(cube, None)];
+        let mut next = 0;
+        // Where the states one move further out than the ones being
+        // looked at start in `found`.
+        let mut next_layer = 1;
+        let mut depth = 0;
+        watch.deepen(depth, 1);
+
+        while next < found.len() {
+            // The threads look at a batch of states at once. Going through
+            // what they saw in order then finds exactly what looking at
+            // one state at a time would have.
+            let end = found.len().min(next + BATCH);
+            let checked = &self.checked;
+            let seen = parallel::map_chunks(&found[next..end], self.threads, |start, states| {
+                let goal_at = states.iter().position(|&(cube, _)| goal(&cube));
+                let onwards = moves_onwards(start, states, generators, metric, |twisty_cube| {
+                    checked.contains(twisty_cube)
+                });
+                (goal_at.map(|i| start + i), onwards)
+            });
+            let goal_at = seen.iter().find_map(|&(goal_at, _)| goal_at);
+            let mut onwards = seen.into_iter().flat_map(|(_, onwards)| onwards).peekable();
+            for index in next..end {
+                if index == next_layer {
+                    depth += 1;
+                    next_layer = found.len();
+                    watch.deepen(depth, found.len() - index);
+                }
+                watch.visit(found.len() - index - 1)?;
+                if goal_at == Some(index - next) {
+                    return Ok(path_to(&found, index));
+                }
+                while let Some((_, twisty_cube, mv)) =
+                    onwards.next_if(|&(from, _, _)| next + from == index)
+                {
+                    if self.checked.insert(twisty_cube) {
+                        found.push((twisty_cube, Some((index, mv))));
+                    }
                 }
             }
+            next = end;
+        }
+        Err(SolveError::Unreachable)
+    }
+}
 
-            twisty_cube = cube;
-            twisty_cube.front_twist();
-            if !self.checked.contains(&twisty_cube) {
-                stack.push_back(twisty_cube);
-                let op_string = "front_twist,";
-                if (op.is_some()) {
-                    op_string = print!("{:?} {}", op, op_string);
-                    operations.push_back(op_string);
-                } else {
-                    operations.push_back(op_string);
+/// The solutions found by `Solver::solutions`, shortest first.
+pub struct Solutions<'a> {
+    pruning: &'a PruningTables,
+    start: u32,
+    /// Length of the solutions being looked for now.
+    length: usize,
+    longest: usize,
+    /// States on the way from `start`, each with the next move to try.
+    stack: Vec<(u32, usize)>,
+    /// Moves from `start` to the last state on `stack`.
+    path: Vec<usize>,
+}
+
+impl<'a> Solutions<'a> {
+    /// Goes on to the state numbered `rank`, unless it is solved or too
+    /// far from solved. Returns the moves there if they are a solution.
+    fn enter(&mut self, rank: u32) -> Option<Algorithm> {
+        let remaining = self.length - self.path.len();
+        if rank == 0 {
+            if remaining == 0 {
+                let moves = self.pruning.tables().moves();
+                let path: Algorithm = self.path.iter().map(|&mv| moves[mv]).collect();
+                let algorithm = path.simplified();
+                // Anything that simplifies to fewer moves turns up at its
+                // own length.
+                if algorithm.length(self.pruning.moves().metric()) == self.length {
+                    return Some(algorithm.conjugate(self.pruning.rotation()));
                 }
             }
+        } else if self.pruning.estimate(rank) <= remaining {
+            self.stack.push((rank, 0));
+            return None;
+        }
+        // Nothing to explore here after all.
+        self.path.pop();
+        None
+    }
+}
+
+impl<'a> Iterator for Solutions<'a> {
+    type Item = Algorithm;
 
-            twisty_cube = cube;
-            twisty_cube.bottom_twist();
-            if !self.checked.contains(&twisty_cube) {
-                stack.push_back(twisty_cube);
-                let op_string = "bottom_twist,";
-                if (op.is_some()) {
-                    op_string = print!("{:?} {}", op, op_string);
-                    operations.push_back(op_string);
-                } else {
-                    operations.push_back(op_string);
+    fn next(&mut self) -> Option<Algorithm> {
+        let moves = self.pruning.tables().moves();
+        let metric = self.pruning.moves().metric();
+        loop {
+            let top = match self.stack.last_mut() {
+                Some(top) => top,
+                None => {
+                    if self.length > self.longest {
+                        return None;
+                    }
+                    let found = self.enter(self.start);
+                    if self.stack.is_empty() {
+                        self.length += 1;
+                    }
+                    if found.is_some() {
+                        return found;
+                    }
+                    continue;
                 }
+            };
+            let (rank, mv) = *top;
+            if mv == moves.len() {
+                self.stack.pop();
+                self.path.pop();
+                if self.stack.is_empty() {
+                    self.length += 1;
+                }
+                continue;
+            }
+            top.1 += 1;
+            let last = self.path.last().map(|&last| moves[last]);
+            if is_redundant(metric, last, moves[mv]) {
+                continue;
+            }
+            self.path.push(mv);
+            let found = self.enter(self.pruning.tables().apply(rank, mv));
+            if found.is_some() {
+                self.path.pop();
+                return found;
             }
         }
-        panic!("Unsolvamable cube oh nose");
     }
 }
 
+/// Looks depth first for a way to solve the state numbered `rank` in at
+/// most `limit` more moves, leaving the moves found so far in `path`.
+fn deepen(
+    pruning: &PruningTables,
+    rank: u32,
+    limit: usize,
+    path: &mut Vec<usize>,
+    watch: &mut Watch,
+) -> Result<bool, SolveError> {
+    if path.is_empty() {
+        // Every shorter limit has been tried already.
+        watch.deepen(limit, 0);
+    }
+    watch.visit(path.len())?;
+    if rank == 0 {
+        return Ok(true);
+    }
+    if pruning.estimate(rank) > limit {
+        return Ok(false);
+    }
+    let moves = pruning.tables().moves();
+    let last = path.last().map(|&mv| moves[mv]);
+    for (mv, &generator) in moves.iter().enumerate() {
+        if is_redundant(pruning.moves().metric(), last, generator) {
+            continue;
+        }
+        path.push(mv);
+        let next = pruning.tables().apply(rank, mv);
+        if deepen(pruning, next, limit - 1, path, watch)? {
+            return Ok(true);
+        }
+        path.pop();
+    }
+    Ok(false)
+}
+
+/// The states one end of a bidirectional search has found.
+struct Frontier {
+    /// Every state found, with the state it was reached from and the move
+    /// that got there, nearest first.
+    found: Vec<(Cube, Option<(usize, Move)>)>,
+    index: HashMap<Cube, usize>,
+    /// Where the states furthest from the start begin in `found`.
+    layer: usize,
+    /// How far those states are from the start.
+    depth: usize,
+}
+
+impl Frontier {
+    fn new(starts: Vec<Cube>) -> Self {
+        Frontier {
+            index: starts
+                .iter()
+                .enumerate()
+                .map(|(i, &cube)| (cube, i))
+                .collect(),
+            found: starts.into_iter().map(|cube| (cube, None)).collect(),
+            layer: 0,
+            depth: 0,
+        }
+    }
+
+    fn layer_len(&self) -> usize {
+        self.found.len() - self.layer
+    }
+
+    /// Finds every state one move further out than the furthest so far.
+    /// If any of them were already found by `other`, returns where the
+    /// two meet closest to `other`'s start, as indices into each side's
+    /// `found`.
+    fn expand(
+        &mut self,
+        metric: Metric,
+        generators: &[Move],
+        other: &Frontier,
+        threads: usize,
+        watch: &mut Watch,
+    ) -> Result<Option<(usize, usize)>, SolveError> {
+        let end = self.found.len();
+        let mut meeting: Option<(usize, usize, usize)> = None;
+        // As in `Solver::search_for`, batches of states are looked at on
+        // all the threads and then gone through in order.
+        for start in (self.layer..end).step_by(BATCH) {
+            let stop = end.min(start + BATCH);
+            let index = &self.index;
+            let seen = parallel::map_chunks(&self.found[start..stop], threads, |from, states| {
+                moves_onwards(from, states, generators, metric, |twisty_cube| {
+                    index.contains_key(twisty_cube)
+                })
+            });
+            let mut onwards = seen.into_iter().flatten().peekable();
+            for index in start..stop {
+                watch.visit(end - index - 1 + other.layer_len())?;
+                while let Some((_, twisty_cube, mv)) =
+                    onwards.next_if(|&(from, _, _)| start + from == index)
+                {
+                    if self.index.contains_key(&twisty_cube) {
+                        continue;
+                    }
+                    self.index.insert(twisty_cube, self.found.len());
+                    self.found.push((twisty_cube, Some((index, mv))));
+
+                    if let Some(&theirs) = other.index.get(&twisty_cube) {
+                        let distance = path_to(&other.found, theirs).len();
+                        if meeting.is_none_or(|(_, _, nearest)| distance < nearest) {
+                            meeting = Some((self.found.len() - 1, theirs, distance));
+                        }
+                    }
+                }
+            }
+        }
+        self.layer = end;
+        self.depth += 1;
+        Ok(meeting.map(|(ours, theirs, _)| (ours, theirs)))
+    }
+}
+
+/// The states one move on from each of `states` that `known` doesn't
+/// already know about, in order. Each comes with where the state it is
+/// reached from is in `states`, plus `start`, and the move to it.
+fn moves_onwards<K>(
+    start: usize,
+    states: &[(Cube, Option<(usize, Move)>)],
+    generators: &[Move],
+    metric: Metric,
+    known: K,
+) -> Vec<(usize, Cube, Move)>
+where
+    K: Fn(&Cube) -> bool,
+{
+    let mut onwards = Vec::new();
+    for (i, &(cube, came_from)) in states.iter().enumerate() {
+        let last = came_from.map(|(_, mv)| mv);
+        for &mv in generators {
+            if is_redundant(metric, last, mv) {
+                continue;
+            }
+            let mut twisty_cube = cube;
+            twisty_cube.apply_move(mv);
+            if !known(&twisty_cube) {
+                onwards.push((start + i, twisty_cube, mv));
+            }
+        }
+    }
+    onwards
+}
+
+/// Follows the trail of moves back from `found[index]` to the start.
+fn path_to(found: &[(Cube, Option<(usize, Move)>)], mut index: usize) -> Algorithm {
+    let mut moves = Vec::new();
+    while let Some((previous, mv)) = found[index].1 {
+        moves.push(mv);
+        index = previous;
+    }
+    moves.reverse();
+    Algorithm::new(moves)
+}
+
 #[cfg(test)]
 mod test {
 
-    use super::{Cube, Solver};
+    use std::sync::{Arc, Mutex};
+
+    use super::{is_redundant, Cube, Hint, MoveSet, SolveError, Solver, Strategy, GENERATORS};
+    use algorithm::Algorithm;
+    use cube::Color;
+    use moves::{Amount, Face, Metric, Move, METRICS};
+    use pattern::Pattern;
+    use pieces::Pieces;
+    use table::DistanceTable;
+    use validate::InvalidCube;
+    use watch::Limits;
+
+    fn assert_solves(cube: Cube, solution: &Algorithm) {
+        let mut replayed = cube;
+        solution.apply(&mut replayed);
+        assert!(replayed.is_solved(), "{} does not solve {}", solution, cube);
+    }
 
     #[test]
-    fn test_solve_is_noop_on_already_solved_cube() {
+    fn test_hint_gives_one_move_closer() {
+        let mut solver = Solver::with_strategy(Strategy::DistanceTable);
+        assert_eq!(
+            solver.hint(Cube::new()).unwrap(),
+            Hint {
+                next: None,
+                distance: 0
+            }
+        );
+        let mut cube = Cube::new();
+        "R U F' R2".parse::<Algorithm>().unwrap().apply(&mut cube);
+        let mut distance = 4;
+        while distance > 0 {
+            let hint = solver.hint(cube).unwrap();
+            assert_eq!(hint.distance, distance);
+            cube.apply_move(hint.next.unwrap());
+            distance -= 1;
+        }
+        assert!(cube.is_solved());
+
+        let mut quarter_turns = Solver::new();
+        quarter_turns.set_metric(Metric::Qtm);
         let mut cube = Cube::new();
+        "R2".parse::<Algorithm>().unwrap().apply(&mut cube);
+        assert_eq!(quarter_turns.hint(cube).unwrap().distance, 2);
+    }
+
+    #[test]
+    fn test_solve_is_noop_on_already_solved_cube() {
+        let cube = Cube::new();
         let mut solver = Solver::new();
-        let result = solver.solve(cube);
+        let result = solver.solve(cube).unwrap();
 
-        assert!(result.is_solved());
+        assert!(result.algorithm.is_empty());
+        assert_eq!(result.nodes, 1);
     }
 
     #[test]
@@ -92,9 +1052,11 @@ mod test {
         let mut cube = Cube::new();
         cube.twist();
         cube.twist();
-        let result = solver.solve(cube);
+        let result = solver.solve(cube).unwrap();
 
-        assert!(result.is_solved());
+        assert_solves(cube, &result.algorithm);
+        assert_eq!(result.htm(), 1);
+        assert_eq!(result.qtm(), 2);
     }
 
     #[test]
@@ -105,9 +1067,10 @@ mod test {
         cube.turn();
         cube.twist();
         cube.twist();
-        let result = solver.solve(cube);
+        let result = solver.solve(cube).unwrap();
 
-        assert!(result.is_solved());
+        assert_solves(cube, &result.algorithm);
+        assert_eq!(result.htm(), 1);
     }
 
     #[test]
@@ -127,8 +1090,512 @@ mod test {
         assert!(!cube.is_solved());
 
         let mut solver = Solver::new();
-        let result = solver.solve(cube);
+        let result = solver.solve(cube).unwrap();
+
+        assert_solves(cube, &result.algorithm);
+        assert!(result.htm() <= 4, "{}", result);
+    }
+
+    #[test]
+    fn test_solution_is_shortest() {
+        let scramble: Algorithm = "R U' F2 L".parse().unwrap();
+        let mut cube = Cube::new();
+        scramble.apply(&mut cube);
+
+        let result = Solver::new().solve(cube).unwrap();
+
+        assert_solves(cube, &result.algorithm);
+        assert_eq!(result.htm(), 4, "{}", result);
+    }
+
+    #[test]
+    fn test_solves_rotated_cube_without_rotations() {
+        let scramble: Algorithm = "x R U' F y2 L D".parse().unwrap();
+        let mut cube = Cube::new();
+        scramble.apply(&mut cube);
+
+        let result = Solver::new().solve(cube).unwrap();
+
+        assert_solves(cube, &result.algorithm);
+        assert_eq!(result.algorithm.htm(), result.algorithm.etm());
+    }
+
+    #[test]
+    fn test_solver_can_be_reused() {
+        let mut solver = Solver::new();
+        let mut cube = Cube::new();
+        "R U".parse::<Algorithm>().unwrap().apply(&mut cube);
+
+        let first = solver.solve(cube).unwrap();
+        let second = solver.solve(cube).unwrap();
+
+        assert_eq!(first.algorithm, second.algorithm);
+    }
+
+    #[test]
+    fn test_invalid_cube_is_rejected() {
+        let mut pieces = Pieces::solved();
+        pieces.orientation[0] = 2;
+
+        assert_eq!(
+            Solver::new().solve(pieces.to_cube()),
+            Err(SolveError::Invalid(InvalidCube::TwistedCorner {
+                slot: 0,
+                twist: 2,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_distance_table_strategy_agrees_with_breadth_first() {
+        let mut breadth_first = Solver::new();
+        let mut table = Solver::with_strategy(Strategy::DistanceTable);
+        for moves in &["", "R2", "R U' F2 L", "x R U' F y2 L D"] {
+            let mut cube = Cube::new();
+            moves.parse::<Algorithm>().unwrap().apply(&mut cube);
+
+            let expected = breadth_first.solve(cube).unwrap();
+            let result = table.solve(cube).unwrap();
+
+            assert_solves(cube, &result.algorithm);
+            assert_eq!(result.htm(), expected.htm(), "{}", moves);
+        }
+    }
+
+    #[test]
+    fn test_ida_star_strategy_agrees_with_breadth_first() {
+        let mut breadth_first = Solver::new();
+        let mut ida_star = Solver::with_strategy(Strategy::IdaStar);
+        for moves in &["", "R2", "R U' F2 L", "x R U' F y2 L D"] {
+            let mut cube = Cube::new();
+            moves.parse::<Algorithm>().unwrap().apply(&mut cube);
+
+            let expected = breadth_first.solve(cube).unwrap();
+            let result = ida_star.solve(cube).unwrap();
+
+            assert_solves(cube, &result.algorithm);
+            assert_eq!(result.htm(), expected.htm(), "{}", moves);
+            assert!(result.nodes <= expected.nodes, "{}", moves);
+        }
+    }
+
+    #[test]
+    fn test_ida_star_solves_deep_scrambles() {
+        let mut solver = Solver::with_strategy(Strategy::IdaStar);
+        let mut cube = Cube::new();
+        "R U R' U' R' F R2 U' R' U' R U R' F'"
+            .parse::<Algorithm>()
+            .unwrap()
+            .apply(&mut cube);
+
+        let result = solver.solve(cube).unwrap();
+
+        assert_solves(cube, &result.algorithm);
+        assert_eq!(result.htm(), DistanceTable::new().distance(&cube).unwrap());
+    }
+
+    #[test]
+    fn test_bidirectional_strategy_agrees_with_breadth_first() {
+        let mut breadth_first = Solver::new();
+        let mut bidirectional = Solver::with_strategy(Strategy::Bidirectional);
+        for moves in &["", "R2", "R U' F2 L", "x R U' F y2 L D"] {
+            let mut cube = Cube::new();
+            moves.parse::<Algorithm>().unwrap().apply(&mut cube);
+
+            let expected = breadth_first.solve(cube).unwrap();
+            let result = bidirectional.solve(cube).unwrap();
+
+            assert_solves(cube, &result.algorithm);
+            assert_eq!(result.htm(), expected.htm(), "{}", moves);
+            assert!(result.nodes <= expected.nodes, "{}", moves);
+        }
+    }
+
+    #[test]
+    fn test_bidirectional_solves_deep_scrambles() {
+        let mut solver = Solver::with_strategy(Strategy::Bidirectional);
+        let mut cube = Cube::new();
+        "R U R' U' R' F R2 U' R' U' R U R' F'"
+            .parse::<Algorithm>()
+            .unwrap()
+            .apply(&mut cube);
+
+        let result = solver.solve(cube).unwrap();
+
+        assert_solves(cube, &result.algorithm);
+        assert_eq!(result.htm(), DistanceTable::new().distance(&cube).unwrap());
+        // Each side only goes about half way, so only thousands of the
+        // millions of states are looked at.
+        assert!(result.nodes < 20_000, "{}", result.nodes);
+    }
+
+    #[test]
+    fn test_every_strategy_is_optimal_in_the_quarter_turn_metric() {
+        let table = DistanceTable::with_metric(Metric::Qtm);
+        for &strategy in &[
+            Strategy::BreadthFirst,
+            Strategy::DistanceTable,
+            Strategy::IdaStar,
+            Strategy::Bidirectional,
+        ] {
+            let mut solver = Solver::with_strategy(strategy);
+            solver.set_metric(Metric::Qtm);
+            for moves in &["R2", "R U2 F'", "x R U' F y2 L2"] {
+                let mut cube = Cube::new();
+                moves.parse::<Algorithm>().unwrap().apply(&mut cube);
+
+                let result = solver.solve(cube).unwrap();
+
+                assert_solves(cube, &result.algorithm);
+                assert_eq!(result.qtm(), table.distance(&cube).unwrap(), "{}", moves);
+                assert_eq!(result.length(Metric::Qtm), result.qtm());
+            }
+        }
+    }
+
+    #[test]
+    fn test_quarter_turn_solutions_merge_half_turns() {
+        let mut solver = Solver::new();
+        solver.set_metric(Metric::Qtm);
+        let mut cube = Cube::new();
+        "R2".parse::<Algorithm>().unwrap().apply(&mut cube);
+
+        let result = solver.solve(cube).unwrap();
+
+        assert_eq!(result.algorithm, "R2".parse().unwrap());
+        assert_eq!(result.qtm(), 2);
+    }
+
+    fn only_turns(solution: &Algorithm, faces: &[Face]) -> bool {
+        solution
+            .moves()
+            .iter()
+            .all(|mv| mv.face().is_some_and(|face| faces.contains(&face)))
+    }
+
+    #[test]
+    fn test_every_strategy_solves_within_a_subgroup() {
+        let cases = [
+            ([Face::R, Face::U], "z2 R2 U'"),
+            ([Face::L, Face::U], "L2 U"),
+            ([Face::D, Face::B], "y D B'"),
+        ];
+        for &strategy in &[
+            Strategy::BreadthFirst,
+            Strategy::DistanceTable,
+            Strategy::IdaStar,
+            Strategy::Bidirectional,
+        ] {
+            for &(faces, scramble) in &cases {
+                let mut solver = Solver::with_strategy(strategy);
+                solver.set_moves(MoveSet::new(&faces, Metric::Htm));
+                let mut cube = Cube::new();
+                scramble.parse::<Algorithm>().unwrap().apply(&mut cube);
+
+                let result = solver.solve(cube).unwrap();
+
+                assert_solves(cube, &result.algorithm);
+                assert!(only_turns(&result.algorithm, &faces), "{}", result);
+                assert_eq!(result.htm(), 2, "{}", scramble);
+
+                // F moves a corner that none of the faces can move back.
+                "F".parse::<Algorithm>().unwrap().apply(&mut cube);
+                assert_eq!(solver.solve(cube), Err(SolveError::Unreachable));
+            }
+        }
+    }
+
+    #[test]
+    fn test_single_face_cannot_mix_arrangement_and_twist() {
+        // R2's arrangement with R's twists: each can be solved by turning
+        // R on its own, but not both at once.
+        let pieces = Pieces {
+            permutation: Pieces::effect_of(Move::Turn(Face::R, Amount::Half)).permutation,
+            orientation: Pieces::effect_of(Move::Turn(Face::R, Amount::Clockwise)).orientation,
+        };
+        let cube = pieces.to_cube();
+        assert!(cube.validate().is_ok());
+        for &metric in &METRICS {
+            for &strategy in &[
+                Strategy::BreadthFirst,
+                Strategy::DistanceTable,
+                Strategy::IdaStar,
+                Strategy::Bidirectional,
+            ] {
+                let mut solver = Solver::with_strategy(strategy);
+                solver.set_moves(MoveSet::new(&[Face::R], metric));
+                assert_eq!(solver.solve(cube), Err(SolveError::Unreachable));
+            }
+            let mut solver = Solver::new();
+            solver.set_moves(MoveSet::new(&[Face::R], metric));
+            assert_eq!(
+                solver.solutions(cube, 0).err(),
+                Some(SolveError::Unreachable)
+            );
+        }
+    }
+
+    #[test]
+    fn test_opposite_faces_are_searched_breadth_first() {
+        let faces = [Face::U, Face::D];
+        for &strategy in &[Strategy::IdaStar, Strategy::Bidirectional] {
+            let mut solver = Solver::with_strategy(strategy);
+            solver.set_moves(MoveSet::new(&faces, Metric::Qtm));
+            let mut cube = Cube::new();
+            "x U D2".parse::<Algorithm>().unwrap().apply(&mut cube);
+
+            let result = solver.solve(cube).unwrap();
+
+            assert_solves(cube, &result.algorithm);
+            assert!(only_turns(&result.algorithm, &faces), "{}", result);
+            // Turning D is turning U with the cube rotated, so `U D2` is
+            // one quarter turn from solved once the cube is held
+            // differently.
+            assert_eq!(result.qtm(), 1);
+
+            "R".parse::<Algorithm>().unwrap().apply(&mut cube);
+            assert_eq!(solver.solve(cube), Err(SolveError::Unreachable));
+        }
+    }
+
+    #[test]
+    fn test_move_set() {
+        let moves = MoveSet::new(&[Face::U, Face::R, Face::U], Metric::Qtm);
+        assert_eq!(moves.faces(), &[Face::U, Face::R]);
+        assert_eq!(moves.to_string(), "<U,R>");
+        assert_eq!(Algorithm::new(moves.moves()), "U U' R R'".parse().unwrap());
+        assert_eq!(MoveSet::all(Metric::Htm).moves(), GENERATORS.to_vec());
+        assert!(MoveSet::new(&[Face::L, Face::R], Metric::Htm)
+            .search_frame()
+            .is_none());
+    }
+
+    #[test]
+    fn test_solutions_of_solved_cube() {
+        let mut solver = Solver::new();
+        let solutions: Vec<Algorithm> = solver.solutions(Cube::new(), 3).unwrap().collect();
+
+        assert_eq!(solutions, vec![Algorithm::default()]);
+    }
+
+    #[test]
+    fn test_solutions_match_brute_force() {
+        let mut cube = Cube::new();
+        "R U' F".parse::<Algorithm>().unwrap().apply(&mut cube);
+        let mut solver = Solver::new();
+        let solutions: Vec<Algorithm> = solver.solutions(cube, 2).unwrap().collect();
+
+        // Every sequence of up to five turns that solves the cube, doesn't
+        // solve it early and doesn't turn a face twice in a row.
+        let mut expected = Vec::new();
+        let mut frontier = vec![(Vec::new(), cube)];
+        for _ in 0..5 {
+            let mut next = Vec::new();
+            for (moves, cube) in frontier {
+                for &mv in &GENERATORS {
+                    if moves.last().and_then(|last: &super::Move| last.face()) == mv.face() {
+                        continue;
+                    }
+                    let mut moves = moves.clone();
+                    moves.push(mv);
+                    let mut cube = cube;
+                    cube.apply_move(mv);
+                    if cube == Cube::new() {
+                        expected.push(Algorithm::new(moves));
+                    } else {
+                        next.push((moves, cube));
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        assert_eq!(solutions.len(), expected.len());
+        for solution in &solutions {
+            assert!(expected.contains(solution), "{}", solution);
+        }
+        assert_eq!(solutions[0].htm(), 3);
+        assert!(solutions
+            .windows(2)
+            .all(|pair| pair[0].htm() <= pair[1].htm()));
+    }
+
+    #[test]
+    fn test_quarter_turn_solutions_are_not_repeated() {
+        let mut cube = Cube::new();
+        "R2 U".parse::<Algorithm>().unwrap().apply(&mut cube);
+        let mut solver = Solver::new();
+        solver.set_metric(Metric::Qtm);
+        let solutions: Vec<Algorithm> = solver.solutions(cube, 4).unwrap().collect();
+
+        // `U' R R` and `U' R' R'` are the same solution, and nothing of
+        // five or seven quarter turns solves this cube without cancelling.
+        assert_eq!(solutions, vec!["U' R2".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_solutions_need_faces_that_do_not_commute() {
+        let mut cube = Cube::new();
+        "U D2".parse::<Algorithm>().unwrap().apply(&mut cube);
+        let mut solver = Solver::new();
+        solver.set_moves(MoveSet::new(&[Face::U, Face::D], Metric::Htm));
+        assert_eq!(
+            solver.solutions(cube, 0).err(),
+            Some(SolveError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_solutions_are_lazy() {
+        let mut cube = Cube::new();
+        "R U R' U' R' F R2 U' R' U' R U R' F'"
+            .parse::<Algorithm>()
+            .unwrap()
+            .apply(&mut cube);
+        let mut solver = Solver::new();
+        let first = solver.solutions(cube, 10).unwrap().next().unwrap();
+
+        assert_solves(cube, &first);
+    }
 
-        assert!(result.is_solved());
+    #[test]
+    fn test_redundant_moves() {
+        let mv = |text: &str| text.parse::<super::Move>().unwrap();
+        assert!(is_redundant(Metric::Htm, Some(mv("R")), mv("R2")));
+        assert!(!is_redundant(Metric::Htm, Some(mv("R")), mv("U")));
+        assert!(!is_redundant(Metric::Qtm, Some(mv("R")), mv("R")));
+        assert!(is_redundant(Metric::Qtm, Some(mv("R'")), mv("R'")));
+        assert!(is_redundant(Metric::Qtm, Some(mv("R")), mv("R'")));
+        // Opposite faces commute, so only U before D is needed.
+        assert!(!is_redundant(Metric::Htm, Some(mv("U")), mv("D2")));
+        assert!(is_redundant(Metric::Htm, Some(mv("D2")), mv("U")));
+        assert!(!is_redundant(Metric::Htm, None, mv("U")));
+    }
+
+    #[test]
+    fn test_solve_to_pattern() {
+        let mut cube = Cube::new();
+        "R U2 F' R U' F"
+            .parse::<Algorithm>()
+            .unwrap()
+            .apply(&mut cube);
+        let mut solver = Solver::new();
+
+        let white_face = Pattern::blank().with_face(Face::D, Color::W);
+        let solution = solver.solve_to(cube, &white_face).unwrap();
+        let mut replayed = cube;
+        solution.algorithm.apply(&mut replayed);
+        assert!(white_face.matches(&replayed));
+        assert!(solution.algorithm.htm() <= solver.solve(cube).unwrap().algorithm.htm());
+
+        let already = solver.solve_to(Cube::new(), &Pattern::solved_faces(&[Face::U]));
+        assert!(already.unwrap().algorithm.is_empty());
+    }
+
+    #[test]
+    fn test_solve_to_unreachable_pattern() {
+        // No cube has two white faces.
+        let two_white = Pattern::blank()
+            .with_face(Face::U, Color::W)
+            .with_face(Face::D, Color::W);
+        let mut solver = Solver::new();
+        solver.set_moves(MoveSet::new(&[Face::U], Metric::Htm));
+        assert_eq!(
+            solver.solve_to(Cube::new(), &two_white).unwrap_err(),
+            SolveError::Unreachable
+        );
+    }
+
+    #[test]
+    fn test_every_strategy_stops_at_the_node_limit() {
+        let mut cube = Cube::new();
+        "R U R' U' R' F R2 U' R' U' R U R' F'"
+            .parse::<Algorithm>()
+            .unwrap()
+            .apply(&mut cube);
+        for &strategy in &[
+            Strategy::BreadthFirst,
+            Strategy::IdaStar,
+            Strategy::Bidirectional,
+        ] {
+            let mut solver = Solver::with_strategy(strategy);
+            solver.set_limits(Limits {
+                nodes: Some(100),
+                ..Limits::default()
+            });
+            match solver.solve(cube) {
+                Err(SolveError::Timeout(progress)) => {
+                    assert_eq!(progress.nodes, 101, "{:?}", strategy);
+                    assert!(progress.depth > 0, "{:?}", strategy);
+                }
+                other => panic!("{:?} didn't time out: {:?}", strategy, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_cancelled_solves() {
+        let mut cube = Cube::new();
+        "R U' F2 L".parse::<Algorithm>().unwrap().apply(&mut cube);
+        let mut solver = Solver::with_strategy(Strategy::IdaStar);
+        let token = solver.cancel_token();
+
+        token.cancel();
+        assert!(matches!(solver.solve(cube), Err(SolveError::Cancelled(_))));
+        assert!(matches!(
+            solver.solve_to(cube, &Pattern::blank()),
+            Err(SolveError::Cancelled(_))
+        ));
+
+        token.reset();
+        assert_solves(cube, &solver.solve(cube).unwrap().algorithm);
+    }
+
+    #[test]
+    fn test_progress_reports() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let mut solver = Solver::new();
+        let sink = reports.clone();
+        solver.set_progress(move |progress| sink.lock().unwrap().push(progress));
+        let mut cube = Cube::new();
+        "R U' F2 L".parse::<Algorithm>().unwrap().apply(&mut cube);
+
+        let result = solver.solve(cube).unwrap();
+
+        let depths: Vec<usize> = reports.lock().unwrap().iter().map(|p| p.depth).collect();
+        assert_eq!(depths, vec![0, 1, 2, 3, 4]);
+        assert!(reports.lock().unwrap()[4].nodes < result.nodes);
+
+        // The callback stays for later solves.
+        reports.lock().unwrap().clear();
+        solver.solve(cube).unwrap();
+        assert_eq!(reports.lock().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_solutions_do_not_depend_on_the_thread_count() {
+        let mut cube = Cube::new();
+        "x R U' F y2 L D"
+            .parse::<Algorithm>()
+            .unwrap()
+            .apply(&mut cube);
+        let white_face = Pattern::blank().with_face(Face::D, Color::W);
+        for &strategy in &[Strategy::BreadthFirst, Strategy::Bidirectional] {
+            let mut expected = None;
+            for &threads in &[1, 2, 5] {
+                let mut solver = Solver::with_strategy(strategy);
+                solver.set_threads(threads);
+                assert_eq!(solver.threads(), threads);
+                let solved = solver.solve(cube).unwrap();
+                let patterned = solver.solve_to(cube, &white_face).unwrap();
+                let found = (
+                    solved.algorithm,
+                    solved.nodes,
+                    patterned.algorithm,
+                    patterned.nodes,
+                );
+                assert_eq!(expected.get_or_insert_with(|| found.clone()), &found);
+            }
+        }
     }
 }
@@ -0,0 +1,593 @@
+use std::io;
+use std::path::Path;
+
+use algorithm::Algorithm;
+use cache::{self, CacheError, TableKind};
+use coord::{MoveTables, ORIENTATIONS, PERMUTATIONS};
+use cube::Cube;
+use moves::Metric;
+use parallel::{self, available_threads};
+use pieces::STATES;
+use solver::{search_start, MoveSet, SolveError};
+
+/// Marks a state the table generator hasn't reached yet.
+const UNKNOWN: u8 = u8::MAX;
+
+/// The number of moves every single state needs to solve, found by one
+/// breadth first search out from solved over the whole state space.
+///
+/// With it any cube can be solved optimally by repeatedly making a move
+/// that takes it one step closer to solved, without any searching.
+pub struct DistanceTable {
+    moves: MoveSet,
+    /// Turns cubes so that the moves searched with are U, R and F turns.
+    rotation: Algorithm,
+    tables: MoveTables,
+    /// Moves to solve, indexed by position number.
+    distances: Vec<u8>,
+}
+
+impl Default for DistanceTable {
+    fn default() -> Self {
+        DistanceTable::new()
+    }
+}
+
+impl DistanceTable {
+    /// Works out the distance to solved of all `STATES` states, counting
+    /// any turn of U, R or F as one move.
+    pub fn new() -> Self {
+        DistanceTable::with_metric(Metric::Htm)
+    }
+
+    /// Works out the distance to solved of all `STATES` states, counting
+    /// moves in `metric`.
+    pub fn with_metric(metric: Metric) -> Self {
+        DistanceTable::for_moves(&MoveSet::all(metric))
+    }
+
+    /// Works out the distance to solved of every state, turning only the
+    /// faces in `moves`. States that can't be solved with them have no
+    /// distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `moves` turns two opposite faces.
+    pub fn for_moves(moves: &MoveSet) -> Self {
+        DistanceTable::build(moves, available_threads())
+    }
+
+    /// Works out the table for `moves` on up to `threads` threads. The
+    /// table comes out the same however many there are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `moves` turns two opposite faces.
+    pub fn build(moves: &MoveSet, threads: usize) -> Self {
+        let (rotation, tables) = search_tables(moves);
+        let count = tables.moves().len();
+        let distances = distances_from_solved(STATES as usize, count, threads, |rank, mv| {
+            tables.apply(rank, mv)
+        });
+        DistanceTable {
+            moves: moves.clone(),
+            rotation,
+            tables,
+            distances,
+        }
+    }
+
+    /// Reads the table for `moves` that `save` wrote to `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `moves` turns two opposite faces.
+    pub fn load(path: &Path, moves: &MoveSet) -> Result<Self, CacheError> {
+        let distances = cache::read(path, TableKind::Distance, moves)?;
+        if distances.len() != STATES as usize {
+            return Err(CacheError::Malformed);
+        }
+        let (rotation, tables) = search_tables(moves);
+        Ok(DistanceTable {
+            moves: moves.clone(),
+            rotation,
+            tables,
+            distances,
+        })
+    }
+
+    /// Writes the table to `path` for `load` to read back.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        cache::write(path, TableKind::Distance, &self.moves, &self.distances)
+    }
+
+    /// Loads the table for `moves` from its file in the cache directory
+    /// `dir`. If the file is missing or damaged the table is worked out
+    /// again on up to `threads` threads and saved there instead, as far as
+    /// it can be.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `moves` turns two opposite faces.
+    pub fn cached(dir: &Path, moves: &MoveSet, threads: usize) -> Self {
+        let path = cache::path(dir, TableKind::Distance, moves);
+        DistanceTable::load(&path, moves).unwrap_or_else(|_| {
+            let table = DistanceTable::build(moves, threads);
+            // Failing to save only means building it again next time.
+            let _ = table.save(&path);
+            table
+        })
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.moves.metric()
+    }
+
+    /// The moves the table was built for.
+    pub fn moves(&self) -> &MoveSet {
+        &self.moves
+    }
+
+    /// The fewest moves that solve `cube`, however it is held, counted in
+    /// the table's metric.
+    pub fn distance(&self, cube: &Cube) -> Result<usize, SolveError> {
+        let rank = search_start(cube, &self.rotation)?.rank();
+        self.distance_of_rank(rank).ok_or(SolveError::Unreachable)
+    }
+
+    /// The distance of the state numbered `rank` by `Pieces::rank`, or
+    /// `None` if the table's moves can't solve it.
+    pub fn distance_of_rank(&self, rank: u32) -> Option<usize> {
+        match self.distances[rank as usize] {
+            UNKNOWN => None,
+            distance => Some(usize::from(distance)),
+        }
+    }
+
+    /// Distance of the state furthest from solved.
+    pub fn max_distance(&self) -> usize {
+        self.distances
+            .iter()
+            .filter(|&&distance| distance != UNKNOWN)
+            .max()
+            .map_or(0, |&distance| usize::from(distance))
+    }
+
+    /// How many states there are at each distance from solved, starting
+    /// with the one solved state.
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.max_distance() + 1];
+        for &distance in &self.distances {
+            if distance != UNKNOWN {
+                counts[usize::from(distance)] += 1;
+            }
+        }
+        counts
+    }
+
+    /// A solution to `cube` that is optimal in the table's metric, without
+    /// any rotations, along with the number of states looked up on the way
+    /// down.
+    pub fn solve(&self, cube: &Cube) -> Result<(Algorithm, usize), SolveError> {
+        let mut rank = search_start(cube, &self.rotation)?.rank();
+        let mut distance = self.distance_of_rank(rank).ok_or(SolveError::Unreachable)?;
+        let generators = self.tables.moves();
+        let mut moves = Vec::with_capacity(distance);
+        let mut nodes = 1;
+        while distance > 0 {
+            let (mv, next) = (0..generators.len())
+                .map(|mv| (mv, self.tables.apply(rank, mv)))
+                .find(|&(_, next)| {
+                    nodes += 1;
+                    self.distance_of_rank(next).is_some_and(|d| d < distance)
+                })
+                .expect("every unsolved state has a neighbour closer to solved");
+            moves.push(generators[mv]);
+            rank = next;
+            distance -= 1;
+        }
+        let algorithm = Algorithm::new(moves).simplified();
+        Ok((algorithm.conjugate(&self.rotation), nodes))
+    }
+}
+
+/// Lower bounds on the distance of every state, from how far its corner
+/// arrangement and its corner twists are from solved on their own. They
+/// take up a few kilobytes instead of the few megabytes of a
+/// `DistanceTable`.
+pub struct PruningTables {
+    moves: MoveSet,
+    rotation: Algorithm,
+    tables: MoveTables,
+    permutation: Vec<u8>,
+    orientation: Vec<u8>,
+}
+
+impl Default for PruningTables {
+    fn default() -> Self {
+        PruningTables::new()
+    }
+}
+
+impl PruningTables {
+    pub fn new() -> Self {
+        PruningTables::with_metric(Metric::Htm)
+    }
+
+    /// Tables whose estimates are in moves counted in `metric`.
+    pub fn with_metric(metric: Metric) -> Self {
+        PruningTables::for_moves(&MoveSet::all(metric))
+    }
+
+    /// Tables for searches turning only the faces in `moves`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `moves` turns two opposite faces.
+    pub fn for_moves(moves: &MoveSet) -> Self {
+        let (rotation, tables) = search_tables(moves);
+        let count = tables.moves().len();
+        // These are too small to be worth sharing out between threads.
+        let permutation = distances_from_solved(PERMUTATIONS, count, 1, |rank, mv| {
+            tables.apply_permutation(rank, mv)
+        });
+        let orientation = distances_from_solved(ORIENTATIONS, count, 1, |rank, mv| {
+            tables.apply_orientation(rank, mv)
+        });
+        PruningTables {
+            moves: moves.clone(),
+            rotation,
+            tables,
+            permutation,
+            orientation,
+        }
+    }
+
+    /// Reads the tables for `moves` that `save` wrote to `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `moves` turns two opposite faces.
+    pub fn load(path: &Path, moves: &MoveSet) -> Result<Self, CacheError> {
+        let mut permutation = cache::read(path, TableKind::Pruning, moves)?;
+        if permutation.len() != PERMUTATIONS + ORIENTATIONS {
+            return Err(CacheError::Malformed);
+        }
+        let orientation = permutation.split_off(PERMUTATIONS);
+        let (rotation, tables) = search_tables(moves);
+        Ok(PruningTables {
+            moves: moves.clone(),
+            rotation,
+            tables,
+            permutation,
+            orientation,
+        })
+    }
+
+    /// Writes the tables to `path` for `load` to read back, the
+    /// arrangement distances first.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let data = [&self.permutation[..], &self.orientation[..]].concat();
+        cache::write(path, TableKind::Pruning, &self.moves, &data)
+    }
+
+    /// Loads the tables for `moves` from their file in the cache directory
+    /// `dir`, working them out again if the file is missing or damaged.
+    /// See `DistanceTable::cached`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `moves` turns two opposite faces.
+    pub fn cached(dir: &Path, moves: &MoveSet) -> Self {
+        let path = cache::path(dir, TableKind::Pruning, moves);
+        PruningTables::load(&path, moves).unwrap_or_else(|_| {
+            let tables = PruningTables::for_moves(moves);
+            let _ = tables.save(&path);
+            tables
+        })
+    }
+
+    /// The moves the tables were built for.
+    pub fn moves(&self) -> &MoveSet {
+        &self.moves
+    }
+
+    /// Turns cubes so that the moves in `tables` are the ones to use.
+    pub fn rotation(&self) -> &Algorithm {
+        &self.rotation
+    }
+
+    /// The U, R and F turns the tables were built with, each costing one
+    /// move in the tables' metric.
+    pub fn tables(&self) -> &MoveTables {
+        &self.tables
+    }
+
+    /// Whether the moves might solve the state numbered `rank`: both its
+    /// arrangement and its twists can be solved on their own. When this is
+    /// false the state can't be solved, but when it is true it still may
+    /// not be, e.g. R2's arrangement with R's twists can't be solved by
+    /// turning R alone. See `distance_bound`.
+    pub fn is_reachable(&self, rank: u32) -> bool {
+        self.permutation[rank as usize / ORIENTATIONS] != UNKNOWN
+            && self.orientation[rank as usize % ORIENTATIONS] != UNKNOWN
+    }
+
+    /// More moves than any state the moves can solve needs. No solution
+    /// passes through the same state twice, so none is as long as the
+    /// number of states that pass `is_reachable`. A search that gets this
+    /// deep without solving the cube never will.
+    pub fn distance_bound(&self) -> usize {
+        let arrangements = self.permutation.iter().filter(|&&d| d != UNKNOWN);
+        let twists = self.orientation.iter().filter(|&&d| d != UNKNOWN);
+        arrangements.count() * twists.count()
+    }
+
+    /// At least as many moves as the state numbered `rank` needs to solve,
+    /// and never more.
+    pub fn estimate(&self, rank: u32) -> usize {
+        let permutation = self.permutation[rank as usize / ORIENTATIONS];
+        let orientation = self.orientation[rank as usize % ORIENTATIONS];
+        usize::from(permutation.max(orientation))
+    }
+}
+
+/// The rotation that lets a search use only U, R and F turns instead of
+/// `moves`, along with tables of those turns.
+fn search_tables(moves: &MoveSet) -> (Algorithm, MoveTables) {
+    let (rotation, searched) = moves
+        .search_frame()
+        .expect("tables need a move set that never turns the DBL slot");
+    (rotation, MoveTables::new(&searched.moves()))
+}
+
+/// Searches breadth first from 0 through `size` numbered states, where
+/// `apply` gives the number of the state reached by each of `moves` moves.
+/// The neighbours of each depth are found on up to `threads` threads.
+fn distances_from_solved<F>(size: usize, moves: usize, threads: usize, apply: F) -> Vec<u8>
+where
+    F: Fn(u32, usize) -> u32 + Sync,
+{
+    let mut distances = vec![UNKNOWN; size];
+    distances[0] = 0;
+    let mut frontier = vec![0];
+    let mut depth = 0;
+    while !frontier.is_empty() {
+        depth += 1;
+        frontier = if threads <= 1 {
+            next_layer(&mut distances, &frontier, depth, moves, &apply)
+        } else {
+            next_layer_in_parallel(&mut distances, &frontier, depth, moves, threads, &apply)
+        };
+    }
+    distances
+}
+
+/// Marks the states one move on from `frontier` that have no distance yet
+/// as `depth` moves away, returning them.
+fn next_layer<F>(
+    distances: &mut [u8],
+    frontier: &[u32],
+    depth: u8,
+    moves: usize,
+    apply: &F,
+) -> Vec<u32>
+where
+    F: Fn(u32, usize) -> u32,
+{
+    let mut next = Vec::new();
+    for &rank in frontier {
+        for mv in 0..moves {
+            let neighbour = apply(rank, mv);
+            if distances[neighbour as usize] == UNKNOWN {
+                distances[neighbour as usize] = depth;
+                next.push(neighbour);
+            }
+        }
+    }
+    next
+}
+
+/// `next_layer` with the neighbours found on up to `threads` threads.
+// Inlining this into `distances_from_solved` makes building a table on one
+// thread a third slower.
+#[inline(never)]
+fn next_layer_in_parallel<F>(
+    distances: &mut [u8],
+    frontier: &[u32],
+    depth: u8,
+    moves: usize,
+    threads: usize,
+    apply: &F,
+) -> Vec<u32>
+where
+    F: Fn(u32, usize) -> u32 + Sync,
+{
+    let known = &*distances;
+    let neighbours = parallel::map_chunks(frontier, threads, |_, ranks| {
+        let mut neighbours = Vec::new();
+        for &rank in ranks {
+            for mv in 0..moves {
+                let neighbour = apply(rank, mv);
+                if known[neighbour as usize] == UNKNOWN {
+                    neighbours.push(neighbour);
+                }
+            }
+        }
+        neighbours
+    });
+    // Marking them in order leaves out the same ones as `next_layer`.
+    let mut next = Vec::new();
+    for neighbour in neighbours.into_iter().flatten() {
+        if distances[neighbour as usize] == UNKNOWN {
+            distances[neighbour as usize] = depth;
+            next.push(neighbour);
+        }
+    }
+    next
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    use super::{DistanceTable, PruningTables};
+    use algorithm::cube_after;
+    use cache::{self, CacheError, TableKind};
+    use cube::Cube;
+    use moves::{Face, Metric};
+    use pieces::{Pieces, STATES};
+    use solver::{MoveSet, SolveError};
+    use validate::InvalidCube;
+
+    #[test]
+    fn test_distance_table() {
+        let table = DistanceTable::new();
+
+        // The well known counts for the half turn metric.
+        assert_eq!(
+            table.counts(),
+            vec![1, 9, 54, 321, 1847, 9992, 50136, 227536, 870072, 1887748, 623800, 2644]
+        );
+        assert_eq!(table.counts().iter().sum::<usize>(), STATES as usize);
+        assert_eq!(table.max_distance(), 11);
+
+        assert_eq!(table.distance(&Cube::new()), Ok(0));
+        assert_eq!(table.distance(&cube_after("x y2")), Ok(0));
+        assert_eq!(table.distance(&cube_after("R U' F2 L")), Ok(4));
+
+        for moves in &[
+            "",
+            "R",
+            "R U' F2 L",
+            "x R U' F y2 L D B2 R'",
+            "R U R' U' R' F R2 U' R' U' R U R' F'",
+        ] {
+            let cube = cube_after(moves);
+            let (solution, nodes) = table.solve(&cube).unwrap();
+            let mut solved = cube;
+            solution.apply(&mut solved);
+
+            assert!(solved.is_solved(), "{} does not solve {}", solution, moves);
+            assert_eq!(solution.htm(), table.distance(&cube).unwrap());
+            assert_eq!(solution.htm(), solution.etm());
+            assert!(nodes <= 9 * solution.len() + 1);
+        }
+
+        let mut pieces = Pieces::solved();
+        pieces.orientation[3] = 1;
+        assert_eq!(
+            table.distance(&pieces.to_cube()),
+            Err(SolveError::Invalid(InvalidCube::TwistedCorner {
+                slot: 3,
+                twist: 1,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_quarter_turn_distance_table() {
+        let table = DistanceTable::with_metric(Metric::Qtm);
+
+        // The well known counts for the quarter turn metric.
+        assert_eq!(
+            table.counts(),
+            vec![
+                1, 6, 27, 120, 534, 2256, 8969, 33058, 114149, 360508, 930588, 1350852, 782536,
+                90280, 276
+            ]
+        );
+        assert_eq!(table.metric(), Metric::Qtm);
+        assert_eq!(table.distance(&cube_after("R2 U")), Ok(3));
+
+        let cube = cube_after("x R U' F y2 L D B2 R'");
+        let (solution, _) = table.solve(&cube).unwrap();
+        let mut solved = cube;
+        solution.apply(&mut solved);
+        assert!(solved.is_solved());
+        assert_eq!(solution.qtm(), table.distance(&cube).unwrap());
+    }
+
+    #[test]
+    fn test_pruning_tables_never_overestimate() {
+        let pruning = PruningTables::new();
+        let table = DistanceTable::new();
+        assert_eq!(pruning.estimate(0), 0);
+        for rank in (0..STATES).step_by(997) {
+            assert!(Some(pruning.estimate(rank)) <= table.distance_of_rank(rank));
+        }
+    }
+
+    #[test]
+    fn test_two_generator_table() {
+        let moves = MoveSet::new(&[Face::R, Face::U], Metric::Htm);
+        let table = DistanceTable::for_moves(&moves);
+        let pruning = PruningTables::for_moves(&moves);
+
+        assert_eq!(table.counts().iter().sum::<usize>(), 29160);
+        assert!(pruning.distance_bound() >= 29160);
+
+        let cube = cube_after("y R U R' U2 R");
+        let (solution, _) = table.solve(&cube).unwrap();
+        let mut solved = cube;
+        solution.apply(&mut solved);
+        assert!(solved.is_solved());
+        assert_eq!(Ok(solution.htm()), table.distance(&cube));
+        assert!(solution
+            .moves()
+            .iter()
+            .all(|mv| mv.face() == Some(Face::R) || mv.face() == Some(Face::U)));
+
+        assert_eq!(
+            table.distance(&cube_after("R U F")),
+            Err(SolveError::Unreachable)
+        );
+    }
+
+    #[test]
+    fn test_tables_are_cached() {
+        let dir = env::temp_dir().join(format!("rustix-test-tables-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let moves = MoveSet::new(&[Face::R, Face::U], Metric::Qtm);
+        let distance_path = cache::path(&dir, TableKind::Distance, &moves);
+        let pruning_path = cache::path(&dir, TableKind::Pruning, &moves);
+        assert!(DistanceTable::load(&distance_path, &moves).is_err());
+
+        // Missing files are built and saved.
+        let table = DistanceTable::cached(&dir, &moves, 2);
+        let pruning = PruningTables::cached(&dir, &moves);
+        let loaded = DistanceTable::load(&distance_path, &moves).unwrap();
+        assert_eq!(loaded.distances, table.distances);
+        assert_eq!(loaded.moves(), &moves);
+        let loaded = PruningTables::load(&pruning_path, &moves).unwrap();
+        assert_eq!(loaded.permutation, pruning.permutation);
+        assert_eq!(loaded.orientation, pruning.orientation);
+
+        // Damaged files are built again.
+        let mut bytes = fs::read(&distance_path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&distance_path, &bytes).unwrap();
+        assert!(matches!(
+            DistanceTable::load(&distance_path, &moves),
+            Err(CacheError::Checksum)
+        ));
+        assert_eq!(
+            DistanceTable::cached(&dir, &moves, 1).distances,
+            table.distances
+        );
+        assert!(DistanceTable::load(&distance_path, &moves).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tables_do_not_depend_on_the_thread_count() {
+        let moves = MoveSet::new(&[Face::R, Face::U], Metric::Htm);
+        let table = DistanceTable::build(&moves, 1);
+        assert_eq!(DistanceTable::build(&moves, 3).distances, table.distances);
+    }
+}
@@ -0,0 +1,240 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use cube::{Color, Cube};
+use pieces::{identify_corner, slot_colors, Pieces, SLOT_FACES};
+
+const COLORS: [Color; 6] = [Color::Y, Color::P, Color::B, Color::R, Color::G, Color::W];
+
+/// Why a cube can't be solved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidCube {
+    /// Every colour should show on exactly four stickers.
+    ColorCount { color: Color, count: usize },
+    /// The stickers of a slot don't belong to any corner, either because
+    /// the colours don't go together or because they are in mirror image
+    /// order.
+    UnknownCorner { slot: usize, colors: [Color; 3] },
+    /// The same corner shows up in two slots.
+    DuplicateCorner { corner: usize, slots: [usize; 2] },
+    /// The corners are twisted by a total of `twist` thirds of a turn,
+    /// which no sequence of moves can do. Twisting the corner at `slot`
+    /// back by `twist` would fix the cube.
+    TwistedCorner { slot: usize, twist: u8 },
+}
+
+/// Names a slot by its faces, e.g. `URF`.
+pub fn slot_name(slot: usize) -> String {
+    SLOT_FACES[slot]
+        .iter()
+        .map(|&face| char::from(face))
+        .collect()
+}
+
+impl Display for InvalidCube {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        match *self {
+            InvalidCube::ColorCount { color, count } => write!(
+                formatter,
+                "there are {} {} stickers instead of 4",
+                count, color
+            ),
+            InvalidCube::UnknownCorner { slot, colors } => write!(
+                formatter,
+                "corner at slot {} ({}) is coloured {}{}{}, which is not a real corner",
+                slot,
+                slot_name(slot),
+                colors[0],
+                colors[1],
+                colors[2]
+            ),
+            InvalidCube::DuplicateCorner { corner, slots } => write!(
+                formatter,
+                "the {} corner is at both slot {} ({}) and slot {} ({})",
+                slot_name(corner),
+                slots[0],
+                slot_name(slots[0]),
+                slots[1],
+                slot_name(slots[1])
+            ),
+            InvalidCube::TwistedCorner { slot, twist } => write!(
+                formatter,
+                "corner at slot {} ({}) is twisted {}",
+                slot,
+                slot_name(slot),
+                if twist == 1 {
+                    "clockwise"
+                } else {
+                    "counter clockwise"
+                }
+            ),
+        }
+    }
+}
+
+impl Error for InvalidCube {}
+
+/// Checks the colours, then the corners, then the twist of `cube`,
+/// stopping at the first problem.
+pub fn validate(cube: &Cube) -> Result<Pieces, InvalidCube> {
+    let stickers: Vec<Color> = (0..8)
+        .flat_map(|slot| slot_colors(cube, slot).to_vec())
+        .collect();
+    if let Some(&color) = stickers.iter().find(|&&color| color == Color::X) {
+        return Err(InvalidCube::ColorCount {
+            color,
+            count: stickers.iter().filter(|&&c| c == color).count(),
+        });
+    }
+    for &color in &COLORS {
+        let count = stickers.iter().filter(|&&c| c == color).count();
+        if count != 4 {
+            return Err(InvalidCube::ColorCount { color, count });
+        }
+    }
+
+    let mut pieces = Pieces::solved();
+    let mut seen_at = [None; 8];
+    for slot in 0..8 {
+        let colors = slot_colors(cube, slot);
+        let (corner, twist) =
+            identify_corner(colors).ok_or(InvalidCube::UnknownCorner { slot, colors })?;
+        if let Some(first) = seen_at[corner as usize] {
+            return Err(InvalidCube::DuplicateCorner {
+                corner: corner as usize,
+                slots: [first, slot],
+            });
+        }
+        seen_at[corner as usize] = Some(slot);
+        pieces.permutation[slot] = corner;
+        pieces.orientation[slot] = twist;
+    }
+
+    let twist = pieces.total_twist();
+    if twist != 0 {
+        // Blame a corner that is twisted by exactly the excess if there is
+        // one, as that is the most likely culprit.
+        let slot = (0..8)
+            .find(|&slot| pieces.orientation[slot] == twist)
+            .or_else(|| (0..8).find(|&slot| pieces.orientation[slot] != 0))
+            .unwrap_or(0);
+        return Err(InvalidCube::TwistedCorner { slot, twist });
+    }
+    Ok(pieces)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::InvalidCube;
+    use algorithm::cube_after;
+    use cube::{Block, Color, Cube};
+    use pieces::Pieces;
+
+    fn with_block(cube: &Cube, slot: usize, block: Block) -> Cube {
+        let mut blocks = [cube.block(0); 8];
+        for (posn, b) in blocks.iter_mut().enumerate() {
+            *b = cube.block(posn);
+        }
+        blocks[slot] = block;
+        Cube::from_blocks(blocks)
+    }
+
+    #[test]
+    fn test_scrambled_cubes_are_valid() {
+        for moves in &["", "R U F'", "R U R' U' x y2 L D B2"] {
+            let cube = cube_after(moves);
+            assert_eq!(cube.validate(), Ok(Pieces::from_cube(&cube).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_twisted_corner_is_reported() {
+        let mut pieces = Pieces::solved();
+        pieces.orientation[5] = 1;
+        let err = pieces.to_cube().validate().unwrap_err();
+
+        assert_eq!(err, InvalidCube::TwistedCorner { slot: 5, twist: 1 });
+        assert_eq!(
+            err.to_string(),
+            "corner at slot 5 (DRB) is twisted clockwise"
+        );
+    }
+
+    #[test]
+    fn test_counter_clockwise_twist_is_reported() {
+        let mut pieces = Pieces::solved();
+        pieces.orientation[2] = 2;
+
+        assert_eq!(
+            pieces.to_cube().validate(),
+            Err(InvalidCube::TwistedCorner { slot: 2, twist: 2 })
+        );
+    }
+
+    #[test]
+    fn test_wrong_colour_count_is_reported() {
+        use cube::Color::*;
+        let cube = with_block(&Cube::new(), 0, Block::new(Y, W, Y, B, R, Y));
+
+        assert_eq!(
+            cube.validate(),
+            Err(InvalidCube::ColorCount { color: Y, count: 6 })
+        );
+    }
+
+    #[test]
+    fn test_inside_colour_is_reported() {
+        use cube::Color::*;
+        let cube = with_block(&Cube::new(), 7, Block::new(Y, X, G, B, R, P));
+
+        assert_eq!(
+            cube.validate(),
+            Err(InvalidCube::ColorCount { color: X, count: 1 })
+        );
+    }
+
+    #[test]
+    fn test_mirrored_corner_is_reported() {
+        use cube::Color::*;
+        // Swapping the back and left stickers of UBL keeps the colour
+        // counts right but makes a corner that doesn't exist.
+        let cube = with_block(&Cube::new(), 0, Block::new(Y, W, P, B, R, G));
+
+        assert_eq!(
+            cube.validate(),
+            Err(InvalidCube::UnknownCorner {
+                slot: 0,
+                colors: [Y, P, G],
+            })
+        );
+    }
+
+    #[test]
+    fn test_duplicate_corner_is_reported() {
+        // UBL and DFR have the same six colours between them as UFR and
+        // DBL, so swapping the first pair for a second copy of the other
+        // keeps the colour counts right.
+        let mut pieces = Pieces::solved();
+        pieces.permutation[0] = 3;
+        pieces.permutation[7] = 4;
+        let err = pieces.to_cube().validate().unwrap_err();
+
+        assert_eq!(
+            err,
+            InvalidCube::DuplicateCorner {
+                corner: 3,
+                slots: [0, 3],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "the URF corner is at both slot 0 (ULB) and slot 3 (URF)"
+        );
+    }
+
+    #[test]
+    fn test_inside_colour_prints() {
+        assert_eq!(Color::X.to_string(), "x");
+    }
+}
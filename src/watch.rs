@@ -0,0 +1,214 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use solver::SolveError;
+
+/// How often, in states looked at, a search checks the clock and whether
+/// it has been cancelled.
+const CHECK_EVERY: usize = 1024;
+
+/// How long a search goes between progress reports while it is working
+/// through one depth.
+const REPORT_EVERY: Duration = Duration::from_secs(1);
+
+/// How far a search has got.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Every solution is at least this many moves long.
+    pub depth: usize,
+    /// Number of states looked at so far.
+    pub nodes: usize,
+    /// Number of states found but not looked at yet. Depth first searches
+    /// don't keep any, so for them this is how deep they are.
+    pub frontier: usize,
+}
+
+/// A way to stop searches from elsewhere, such as another thread. Clones
+/// share the same flag, and once it is cancelled every search watching it
+/// stops until it is reset.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Lets searches run again.
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::Relaxed);
+    }
+}
+
+/// When to give up on a search. Searches have no limits by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// How long a search may take.
+    pub time: Option<Duration>,
+    /// How many states a search may look at.
+    pub nodes: Option<usize>,
+}
+
+/// Called with the progress of a search every so often.
+pub type ProgressCallback = Box<dyn FnMut(Progress) + Send>;
+
+/// Keeps an eye on a search: counts the states it looks at, reports on how
+/// it is doing and stops it when it runs out of time or states or is
+/// cancelled.
+pub struct Watch {
+    start: Instant,
+    limits: Limits,
+    cancel: CancelToken,
+    callback: Option<ProgressCallback>,
+    progress: Progress,
+    last_report: Instant,
+}
+
+impl Watch {
+    /// Starts the clock on a search.
+    pub fn new(limits: Limits, cancel: CancelToken, callback: Option<ProgressCallback>) -> Self {
+        let start = Instant::now();
+        Watch {
+            start,
+            limits,
+            cancel,
+            callback,
+            progress: Progress::default(),
+            last_report: start,
+        }
+    }
+
+    /// A watch that never stops a search or reports on it.
+    pub fn unlimited() -> Self {
+        Watch::new(Limits::default(), CancelToken::new(), None)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.progress.nodes
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Hands back the callback for the next search.
+    pub fn into_callback(self) -> Option<ProgressCallback> {
+        self.callback
+    }
+
+    /// Counts one more state looked at, with `frontier` still to look at.
+    /// Fails once the search should stop.
+    pub fn visit(&mut self, frontier: usize) -> Result<(), SolveError> {
+        self.progress.nodes += 1;
+        self.progress.frontier = frontier;
+        if self
+            .limits
+            .nodes
+            .is_some_and(|nodes| self.progress.nodes > nodes)
+        {
+            return Err(SolveError::Timeout(self.progress));
+        }
+        if self.progress.nodes.is_multiple_of(CHECK_EVERY) {
+            self.check()?;
+            if self.last_report.elapsed() >= REPORT_EVERY {
+                self.report();
+            }
+        }
+        Ok(())
+    }
+
+    /// Notes that every solution is at least `depth` moves long, with
+    /// `frontier` states to look at next, and reports it.
+    pub fn deepen(&mut self, depth: usize, frontier: usize) {
+        self.progress.depth = depth;
+        self.progress.frontier = frontier;
+        self.report();
+    }
+
+    /// Fails if the search has been cancelled or has run out of time.
+    pub fn check(&self) -> Result<(), SolveError> {
+        if self.cancel.is_cancelled() {
+            return Err(SolveError::Cancelled(self.progress));
+        }
+        if self.limits.time.is_some_and(|time| self.elapsed() > time) {
+            return Err(SolveError::Timeout(self.progress));
+        }
+        Ok(())
+    }
+
+    fn report(&mut self) {
+        self.last_report = Instant::now();
+        if let Some(ref mut callback) = self.callback {
+            callback(self.progress);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::Duration;
+
+    use super::{CancelToken, Limits, Progress, Watch};
+    use solver::SolveError;
+
+    #[test]
+    fn test_node_limit() {
+        let limits = Limits {
+            nodes: Some(3),
+            ..Limits::default()
+        };
+        let mut watch = Watch::new(limits, CancelToken::new(), None);
+        watch.deepen(2, 5);
+        for _ in 0..3 {
+            assert_eq!(watch.visit(4), Ok(()));
+        }
+        let stopped = Progress {
+            depth: 2,
+            nodes: 4,
+            frontier: 4,
+        };
+        assert_eq!(watch.visit(4), Err(SolveError::Timeout(stopped)));
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let limits = Limits {
+            time: Some(Duration::from_secs(0)),
+            ..Limits::default()
+        };
+        let watch = Watch::new(limits, CancelToken::new(), None);
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(matches!(watch.check(), Err(SolveError::Timeout(_))));
+        assert_eq!(Watch::unlimited().check(), Ok(()));
+    }
+
+    #[test]
+    fn test_cancel_token() {
+        let token = CancelToken::new();
+        let watch = Watch::new(Limits::default(), token.clone(), None);
+        assert_eq!(watch.check(), Ok(()));
+
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(watch.check(), Err(SolveError::Cancelled(_))));
+
+        token.reset();
+        assert_eq!(watch.check(), Ok(()));
+    }
+}